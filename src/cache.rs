@@ -1,28 +1,71 @@
-use std::env;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
 
-use walkdir::{WalkDir, WalkDirIterator, DirEntry};
+use walkdir::WalkDir;
 
+use collate;
+use config;
 use error::TealdeerError::{self, CacheError};
-use types::OsType;
+use lookup::{self, is_page_name, LanguageDirs, PageDirs};
+use search;
+use types::{OsType, PageInfo};
 
 #[derive(Debug)]
 pub struct Cache {
     url: String,
     os: OsType,
+    languages: Vec<String>,
+    page_dir: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+}
+
+/// The pages of one platform directory in one language.
+#[derive(Debug)]
+pub struct Section {
+    /// The language of the pages, like `en` or `de`.
+    pub language: String,
+    /// The name of the platform directory, like `common` or `linux`.
+    pub platform: String,
+    /// The names and paths of the pages, in natural order.
+    pub pages: Vec<(String, PathBuf)>,
 }
 
 impl Cache {
-    pub fn new<S>(url: S, os: OsType) -> Cache where S: Into<String> {
+    /// Create a new cache for the pages in `languages`, in order of
+    /// preference. Pages of a later language are only used if there is no
+    /// page in an earlier one. `en` stands for the untranslated pages,
+    /// which are also used if `languages` is empty.
+    pub fn new<S>(url: S, os: OsType, languages: Vec<String>) -> Cache where S: Into<String> {
+        let languages = if languages.is_empty() { vec!["en".to_string()] } else { languages };
         Cache {
             url: url.into(),
             os: os,
+            languages: languages,
+            page_dir: None,
+            data_dir: None,
         }
     }
 
+    /// Use `page_dir` instead of `$TLDR_PAGE_DIR` and `data_dir` instead of
+    /// `config::data_dir()`, so that tests don't depend on the environment.
+    #[cfg(test)]
+    pub fn with_dirs(mut self, page_dir: &Path, data_dir: &Path) -> Cache {
+        self.page_dir = Some(page_dir.to_path_buf());
+        self.data_dir = Some(data_dir.to_path_buf());
+        self
+    }
+
     /// Return the path to the page directory.
+    ///
+    /// References to other environment variables in `$TLDR_PAGE_DIR`
+    /// (e.g. `${HOME}/tldr/pages`) are expanded.
     fn get_page_dir(&self) -> Result<PathBuf, TealdeerError> {
-        if let Ok(value) = env::var("TLDR_PAGE_DIR") {
+        if let Some(ref dir) = self.page_dir {
+            return Ok(dir.clone());
+        }
+        if let Some(value) = try!(config::var("TLDR_PAGE_DIR").map_err(CacheError)) {
             let path = PathBuf::from(value);
 
             if path.exists() && path.is_dir() {
@@ -37,106 +80,488 @@ impl Cache {
         return Err(CacheError("$TLDR_PAGES_DIR isn't set.".into()));
     }
 
-    /// Return the platform directory.
-    fn get_platform_dir(&self) -> Option<&'static str> {
-        match self.os {
-            OsType::Linux => Some("linux"),
-            OsType::OsX => Some("osx"),
-            OsType::SunOs => None, // TODO: Does Rust support SunOS?
-            OsType::Other => None,
-        }
+    /// Return the page directories of the platform and languages of this
+    /// cache, including the overlay directories of locally edited pages.
+    fn get_page_dirs(&self) -> Result<PageDirs, TealdeerError> {
+        self.get_page_dirs_of(self.languages.clone())
     }
 
-    /// Search for a page and return the path to it.
-    pub fn find_page(&self, name: &str) -> Option<PathBuf> {
-        // Build page file name
-        let page_filename = format!("{}.md", name);
+    /// Return the page directories of the platform of this cache in
+    /// `languages`, including the overlay directories.
+    fn get_page_dirs_of(&self, languages: Vec<String>) -> Result<PageDirs, TealdeerError> {
+        let page_dir = try!(self.get_page_dir());
+        let page_dirs = PageDirs::new(page_dir, self.os, languages);
+        Ok(match self.get_overlay_root() {
+            Some(overlay_dir) => page_dirs.with_overlay_dir(overlay_dir),
+            None => page_dirs,
+        })
+    }
+
+    /// Return all languages that have pages: `en` for the page directory
+    /// itself, followed by the language of each `pages.<language>`
+    /// directory next to it.
+    fn get_all_languages(&self) -> Result<Vec<String>, TealdeerError> {
+        let page_dir = try!(self.get_page_dir());
+        let base = page_dir.file_name().and_then(|name| name.to_str()).unwrap_or("").to_string();
+        let mut languages = vec!["en".to_string()];
+        let entries = match page_dir.parent().map(fs::read_dir) {
+            Some(Ok(entries)) => entries,
+            _ => return Ok(languages),
+        };
+        let mut translated = entries.filter_map(|entry| entry.ok())
+                                    .filter(|entry| entry.path().is_dir())
+                                    .filter_map(|entry| entry.file_name().to_str().map(|name| name.to_string()))
+                                    .filter_map(|name| {
+                                        let prefix = format!("{}.", base);
+                                        name.strip_prefix(&prefix[..]).map(|language| language.to_string())
+                                    })
+                                    .filter(|language| !language.is_empty() && is_page_name(language))
+                                    .collect::<Vec<_>>();
+        translated.sort();
+        languages.extend(translated);
+        Ok(languages)
+    }
+
+    /// Return the directories of the languages that have pages, in order of
+    /// preference (see `PageDirs::language_dirs`).
+    fn get_language_dirs(&self) -> Result<Vec<LanguageDirs>, TealdeerError> {
+        try!(self.get_page_dirs()).language_dirs().map_err(CacheError)
+    }
+
+    /// Return the directories to search for pages, in order of precedence
+    /// (see `PageDirs::search_dirs`).
+    fn get_search_dirs(&self) -> Result<Vec<PathBuf>, TealdeerError> {
+        try!(self.get_page_dirs()).search_dirs().map_err(CacheError)
+    }
+
+    /// Search for a page and return the path to it (see `PageDirs::find_page`).
+    pub fn find_page(&self, name: &str, ignore_case: bool) -> Option<PathBuf> {
+        self.get_page_dirs().ok().and_then(|page_dirs| page_dirs.find_page(name, ignore_case))
+    }
+
+    /// Return the directory that holds the locally edited pages.
+    ///
+    /// Edited pages are kept apart from the page directory, so that they
+    /// survive updates of it and take precedence at lookup time. Each
+    /// language, including English, has its own subdirectory.
+    fn get_overlay_root(&self) -> Option<PathBuf> {
+        self.data_dir.clone()
+            .or_else(config::data_dir)
+            .map(|dir| dir.join("overlay"))
+    }
+
+    /// Return the directory that holds locally edited pages of `language`.
+    fn get_overlay_dir(&self, language: &str) -> Option<PathBuf> {
+        self.get_overlay_root().map(|dir| dir.join(language))
+    }
+
+    /// Return the path to the page `name` in the "common" directory, if it exists.
+    pub fn find_common_page(&self, name: &str) -> Option<PathBuf> {
+        self.get_page_dirs().ok().and_then(|page_dirs| page_dirs.find_common_page(name))
+    }
+
+    /// Search for a page with exactly the given name and return the path to it.
+    fn find_page_exact(&self, name: &str) -> Option<PathBuf> {
+        self.get_page_dirs().ok().and_then(|page_dirs| page_dirs.find_page_exact(name))
+    }
+
+    /// Return the language of the page at `path`.
+    pub fn page_language(&self, path: &Path) -> String {
+        let language_dirs = self.get_language_dirs().unwrap_or_else(|_| vec![]);
+        language_dirs.iter()
+                     .find(|dirs| path.starts_with(&dirs.pages) || dirs.relative_overlay_path(path).is_some())
+                     .map(|dirs| dirs.language.clone())
+                     .unwrap_or_else(|| self.languages[0].clone())
+    }
 
-        // Get platform dir
-        let platforms_dir = match self.get_page_dir() {
-            Ok(cache_dir) => cache_dir,
+    /// Return the path of a locally edited page in the page directory,
+    /// if `path` is in the overlay directory and such a page exists.
+    pub fn find_upstream_page(&self, path: &Path) -> Option<PathBuf> {
+        let language_dirs = match self.get_language_dirs() {
+            Ok(language_dirs) => language_dirs,
             _ => return None,
         };
+        language_dirs.iter()
+                     .filter_map(|dirs| dirs.relative_overlay_path(path).map(|relative| dirs.pages.join(relative)))
+                     .find(|upstream| upstream.is_file())
+    }
 
-        // Determine platform
-        let platform = self.get_platform_dir();
+    /// Return the path of the page in the page directory if the locally
+    /// edited page at `path` shadows a newer version of it.
+    pub fn find_newer_upstream_page(&self, path: &Path) -> Option<PathBuf> {
+        let upstream = match self.find_upstream_page(path) {
+            Some(upstream) => upstream,
+            None => return None,
+        };
+        let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+        match (modified(path), modified(&upstream)) {
+            (Some(local), Some(remote)) if remote > local => Some(upstream),
+            _ => None,
+        }
+    }
 
-        // Search for the page in the platform specific directory
-        if let Some(pf) = platform {
-            let path = platforms_dir.join(&pf).join(&page_filename);
-            if path.exists() && path.is_file() {
-                return Some(path);
+    /// Return the path of the page to edit in the overlay directory. If it
+    /// does not exist there yet, the page is copied from the page directory
+    /// first (if it exists).
+    pub fn prepare_page_to_edit(&self, name: &str) -> Result<PathBuf, TealdeerError> {
+        if !is_page_name(name) {
+            return Err(CacheError(format!("Invalid page name: {}", name)));
+        }
+        let language_dirs = try!(self.get_language_dirs());
+
+        // Edit the page that would be shown (in its language), or create a
+        // new common page in the preferred language
+        let page = self.find_page_exact(name);
+        if let Some(ref page) = page {
+            if language_dirs.iter().any(|dirs| dirs.relative_overlay_path(page).is_some()) {
+                return Ok(page.clone());
             }
         }
+        let (dirs, relative) = page.as_ref()
+                                   .and_then(|page| language_dirs.iter().filter_map(|dirs| {
+                                       page.strip_prefix(&dirs.pages).ok().map(|relative| (dirs, relative.to_path_buf()))
+                                   }).next())
+                                   .unwrap_or_else(|| (&language_dirs[0], Path::new("common").join(format!("{}.md", name))));
+        let overlay_dir = try!(dirs.overlay.clone().ok_or_else(|| {
+            CacheError("Could not determine where to keep edited pages.".into())
+        }));
+        let path = overlay_dir.join(relative);
+
+        if let Some(dir) = path.parent() {
+            try!(fs::create_dir_all(dir).map_err(|e| {
+                CacheError(format!("Could not create {}: {}", dir.display(), e))
+            }));
+        }
+        if let Some(page) = page {
+            try!(fs::copy(&page, &path).map_err(|e| {
+                CacheError(format!("Could not copy {}: {}", page.display(), e))
+            }));
+        }
+        Ok(path)
+    }
 
-        // If platform is not supported or if platform specific page does not exist,
-        // look up the page in the "common" directory.
-        let path = platforms_dir.join("common").join(&page_filename);
 
-        // Return it if it exists, otherwise give up and return `None`
-        if path.exists() && path.is_file() {
-            Some(path)
-        } else {
-            None
+    /// Return information about all available pages.
+    pub fn list_page_infos(&self) -> Result<Vec<PageInfo>, TealdeerError> {
+        let pages = try!(self.list_page_paths());
+        Ok(pages.into_iter().map(|(name, path)| self.page_info(name, path)).collect())
+    }
+
+    /// Return information about the page `name` at `path`.
+    pub fn page_info(&self, name: String, path: PathBuf) -> PageInfo {
+        let platform = path.parent()
+                           .and_then(|dir| dir.file_name())
+                           .and_then(|dir| dir.to_str())
+                           .unwrap_or("")
+                           .to_string();
+        let metadata = search::page_metadata(&path);
+        PageInfo {
+            name: name,
+            platform: platform,
+            language: self.page_language(&path),
+            path: path.display().to_string(),
+            author: metadata.author,
+            tags: metadata.tags,
+            related: metadata.related,
         }
     }
 
-    /// Search for a page and return the path to it, whether or not the path is exists.
-    pub fn find_page_to_edit(&self, name: &str) -> Option<PathBuf> {
+
+    /// Delete the locally edited copies of the page `name`, so that the
+    /// page from the page directory is used again.
+    ///
+    /// Return the number of deleted pages.
+    pub fn reset_page(&self, name: &str) -> Result<usize, TealdeerError> {
+        if !is_page_name(name) {
+            return Err(CacheError(format!("Invalid page name: {}", name)));
+        }
         let page_filename = format!("{}.md", name);
-        let platforms_dir = match self.get_page_dir() {
-            Ok(cache_dir) => cache_dir,
-            _ => return None,
-        };
-        let path = platforms_dir.join("common").join(&page_filename);
-        Some(path)
+        let mut count = 0;
+        for overlay_dir in self.languages.iter().filter_map(|language| self.get_overlay_dir(language)) {
+            for dir in lookup::platform_dirs(self.os).iter().chain(["common"].iter()) {
+                let path = overlay_dir.join(dir).join(&page_filename);
+                if path.is_file() {
+                    try!(fs::remove_file(&path).map_err(|e| {
+                        CacheError(format!("Could not delete {}: {}", path.display(), e))
+                    }));
+                    count += 1;
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Delete the locally edited copies of the pages `names`.
+    ///
+    /// Return the number of deleted pages.
+    pub fn reset_pages(&self, names: &[String]) -> Result<usize, TealdeerError> {
+        let mut count = 0;
+        for name in names {
+            count += try!(self.reset_page(name));
+        }
+        Ok(count)
+    }
+
+    /// Delete all locally edited pages.
+    ///
+    /// Return the number of deleted pages.
+    pub fn reset_all_pages(&self) -> Result<usize, TealdeerError> {
+        let mut count = 0;
+        for overlay_dir in self.languages.iter().filter_map(|language| self.get_overlay_dir(language)) {
+            if !overlay_dir.is_dir() {
+                continue;
+            }
+            count += WalkDir::new(&overlay_dir)
+                             .into_iter()
+                             .filter_map(|e| e.ok())
+                             .filter(|e| e.file_type().is_file())
+                             .count();
+            try!(fs::remove_dir_all(&overlay_dir).map_err(|e| {
+                CacheError(format!("Could not delete {}: {}", overlay_dir.display(), e))
+            }));
+        }
+        Ok(count)
+    }
+
+    /// Return the pages of every platform directory in every language, not
+    /// only those of this platform and languages. Locally edited pages take
+    /// precedence over the pages they shadow.
+    pub fn list_sections(&self) -> Result<Vec<Section>, TealdeerError> {
+        let mut sections = vec![];
+        for language in try!(self.get_all_languages()) {
+            let page_dirs = try!(self.get_page_dirs_of(vec![language.clone()]));
+            for dirs in try!(page_dirs.language_dirs().map_err(CacheError)) {
+                // Look at the page directory first, so that edited pages
+                // replace its pages
+                let roots = dirs.roots().into_iter().rev().collect::<Vec<_>>();
+                let mut platforms = roots.iter()
+                                         .filter_map(|root| fs::read_dir(root).ok())
+                                         .flat_map(|entries| entries.filter_map(|entry| entry.ok()))
+                                         .filter(|entry| entry.path().is_dir())
+                                         .filter_map(|entry| entry.file_name().to_str().map(|name| name.to_string()))
+                                         .collect::<Vec<_>>();
+                platforms.sort();
+                platforms.dedup();
+                for platform in platforms {
+                    let mut pages = BTreeMap::new();
+                    for dir in roots.iter().map(|root| root.join(&platform)).filter(|dir| dir.is_dir()) {
+                        pages.extend(pages_in_dir(&dir));
+                    }
+                    if pages.is_empty() {
+                        continue;
+                    }
+                    let mut pages = pages.into_iter().collect::<Vec<_>>();
+                    pages.sort_by(|a, b| collate::natural_cmp(&a.0, &b.0));
+                    sections.push(Section {
+                        language: dirs.language.clone(),
+                        platform: platform,
+                        pages: pages,
+                    });
+                }
+            }
+        }
+        Ok(sections)
     }
 
     /// Return the available pages.
     pub fn list_pages(&self) -> Result<Vec<String>, TealdeerError> {
-        // Determine platforms directory and platform
-        let platforms_dir = try!(self.get_page_dir());
-        let platform_dir = self.get_platform_dir();
-
-        // Closure that allows the WalkDir instance to traverse platform
-        // specific and common page directories, but not others.
-        let should_walk = |entry: &DirEntry| -> bool {
-            let file_type = entry.file_type();
-            let file_name = match entry.file_name().to_str() {
-                Some(name) => name,
-                None => return false,
+        let pages = try!(self.list_page_paths());
+        Ok(pages.into_iter().map(|(name, _)| name).collect())
+    }
+
+    /// Return the available pages together with the path to each of them,
+    /// in natural order (see `collate::natural_cmp`). Symlinks are followed.
+    ///
+    /// If a page exists in several directories, the path that a lookup
+    /// finds is returned (see `get_search_dirs`).
+    pub fn list_page_paths(&self) -> Result<Vec<(String, PathBuf)>, TealdeerError> {
+        // Determine page directories in order of precedence
+        let dirs = try!(self.get_search_dirs());
+
+        // Read the directories in parallel, which helps on network filesystems.
+        // The rank of a page is the position of its directory in the order
+        // of precedence.
+        let mut handles = vec![];
+        for (rank, path) in dirs.into_iter().enumerate() {
+            if !path.is_dir() {
+                continue;
+            }
+            handles.push(thread::spawn(move || (rank, pages_in_dir(&path))));
+        }
+
+        let mut pages: BTreeMap<String, (usize, PathBuf)> = BTreeMap::new();
+        for handle in handles {
+            let (rank, dir_pages) = match handle.join() {
+                Ok(result) => result,
+                Err(_) => continue,
             };
-            if file_type.is_dir() {
-                if file_name == "common" {
-                    return true;
-                }
-                if let Some(platform) = platform_dir {
-                    return file_name == platform;
+            for (name, path) in dir_pages {
+                if let Some(&(existing_rank, _)) = pages.get(&name) {
+                    if existing_rank <= rank {
+                        continue;
+                    }
                 }
-            } else if file_type.is_file() {
-                return true
+                let _ = pages.insert(name, (rank, path));
             }
-            false
-        };
-
-        // Recursively walk through common and (if applicable) platform specific directory
-        let mut pages = WalkDir::new(platforms_dir)
-                                .min_depth(1) // Skip root directory
-                                .into_iter()
-                                .filter_entry(|e| should_walk(e)) // Filter out pages for other architectures
-                                .filter_map(|e| e.ok()) // Convert results to options, filter out errors
-                                .filter_map(|e| {
-                                    let path = e.path();
-                                    let extension = &path.extension().and_then(|s| s.to_str()).unwrap_or("");
-                                    if e.file_type().is_file() && extension == &"md" {
-                                        path.file_stem().and_then(|stem| stem.to_str().map(|s| s.into()))
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect::<Vec<String>>();
-        pages.sort();
-        pages.dedup();
+        }
+        let mut pages = pages.into_iter().map(|(name, (_, path))| (name, path)).collect::<Vec<_>>();
+        pages.sort_by(|a, b| collate::natural_cmp(&a.0, &b.0));
         Ok(pages)
     }
 }
+
+/// Return the names and paths of the pages in `dir`. Symlinked pages are
+/// included.
+fn pages_in_dir(dir: &Path) -> Vec<(String, PathBuf)> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Could not read {}: {}", dir.display(), e);
+            return vec![];
+        },
+    };
+    entries.filter_map(|entry| entry.ok())
+           .map(|entry| entry.path())
+           .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("md") && path.is_file())
+           .filter_map(|path| {
+               let name = path.file_stem().and_then(|stem| stem.to_str()).map(|stem| stem.to_string());
+               name.map(|name| (name, path))
+           })
+           .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::{self, File};
+    use std::io::{Read, Write};
+    use std::path::Path;
+
+    use tempdir::TempDir;
+
+    use types::OsType;
+    use super::Cache;
+
+    /// Write a page with `content` to `path`, creating its directory.
+    fn write_page(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        File::create(path).unwrap().write_all(content.as_bytes()).unwrap();
+    }
+
+    fn read_page(path: &Path) -> String {
+        let mut content = String::new();
+        let _ = File::open(path).unwrap().read_to_string(&mut content).unwrap();
+        content
+    }
+
+    /// Return a cache of the Linux pages in `languages` below `root`.
+    fn test_cache(root: &Path, languages: &[&str]) -> Cache {
+        let languages = languages.iter().map(|language| language.to_string()).collect();
+        Cache::new("", OsType::Linux, languages).with_dirs(&root.join("pages"), &root.join("data"))
+    }
+
+    #[test]
+    fn test_edited_page_precedence() {
+        let root = TempDir::new("tealdeer-cache").unwrap();
+        let upstream = root.path().join("pages/common/tar.md");
+        let overlay = root.path().join("data/overlay/en/common/tar.md");
+        write_page(&upstream, "# tar\n");
+        let cache = test_cache(root.path(), &["en"]);
+        assert_eq!(cache.find_page("tar", false), Some(upstream.clone()));
+
+        // Editing copies the page to the overlay, which takes precedence
+        assert_eq!(cache.prepare_page_to_edit("tar").unwrap(), overlay);
+        assert_eq!(read_page(&overlay), "# tar\n");
+        assert_eq!(cache.find_page("tar", false), Some(overlay.clone()));
+        assert_eq!(cache.find_upstream_page(&overlay), Some(upstream.clone()));
+        assert_eq!(cache.list_page_paths().unwrap(), vec![("tar".to_string(), overlay.clone())]);
+
+        // Editing again keeps the edited page
+        write_page(&overlay, "# tar\n\n> Edited.\n");
+        assert_eq!(cache.prepare_page_to_edit("tar").unwrap(), overlay);
+        assert_eq!(read_page(&overlay), "# tar\n\n> Edited.\n");
+    }
+
+    #[test]
+    fn test_prepare_new_page_to_edit() {
+        let root = TempDir::new("tealdeer-cache").unwrap();
+        fs::create_dir_all(root.path().join("pages/common")).unwrap();
+        let cache = test_cache(root.path(), &["en"]);
+        let path = cache.prepare_page_to_edit("new").unwrap();
+        assert_eq!(path, root.path().join("data/overlay/en/common/new.md"));
+        assert!(path.parent().unwrap().is_dir());
+        assert!(!path.exists());
+        assert!(cache.prepare_page_to_edit("../new").is_err());
+    }
+
+    #[test]
+    fn test_reset_pages() {
+        let root = TempDir::new("tealdeer-cache").unwrap();
+        let upstream = root.path().join("pages/common/tar.md");
+        write_page(&upstream, "# tar\n");
+        write_page(&root.path().join("data/overlay/en/common/tar.md"), "# tar\n");
+        write_page(&root.path().join("data/overlay/en/linux/ip.md"), "# ip\n");
+        write_page(&root.path().join("data/overlay/de/common/tar.md"), "# tar\n");
+        let cache = test_cache(root.path(), &["en"]);
+
+        assert_eq!(cache.reset_page("tar").unwrap(), 1);
+        assert_eq!(cache.reset_page("tar").unwrap(), 0);
+        assert_eq!(cache.find_page("tar", false), Some(upstream));
+
+        // Edited pages of other languages are left alone
+        assert_eq!(cache.reset_all_pages().unwrap(), 1);
+        assert!(!root.path().join("data/overlay/en").exists());
+        assert!(root.path().join("data/overlay/de/common/tar.md").is_file());
+    }
+
+    #[test]
+    fn test_language_fallback() {
+        let root = TempDir::new("tealdeer-cache").unwrap();
+        let tar_de = root.path().join("pages.de/common/tar.md");
+        let tar_en = root.path().join("pages/common/tar.md");
+        let ls_en = root.path().join("pages/linux/ls.md");
+        write_page(&tar_de, "# tar\n");
+        write_page(&tar_en, "# tar\n");
+        write_page(&ls_en, "# ls\n");
+
+        // The first language with a page wins, for every page on its own
+        let cache = test_cache(root.path(), &["de", "en"]);
+        assert_eq!(cache.find_page("tar", false), Some(tar_de.clone()));
+        assert_eq!(cache.find_page("ls", false), Some(ls_en.clone()));
+        assert_eq!(cache.list_page_paths().unwrap(),
+                   vec![("ls".to_string(), ls_en.clone()), ("tar".to_string(), tar_de.clone())]);
+        assert_eq!(cache.page_language(&tar_de), "de");
+        assert_eq!(cache.page_language(&ls_en), "en");
+
+        // Languages without pages are skipped
+        let cache = test_cache(root.path(), &["fr", "en", "de"]);
+        assert_eq!(cache.find_page("tar", false), Some(tar_en));
+        assert!(test_cache(root.path(), &["fr"]).list_page_paths().is_err());
+    }
+
+    #[test]
+    fn test_language_fallback_edited_pages() {
+        let root = TempDir::new("tealdeer-cache").unwrap();
+        write_page(&root.path().join("pages.de/common/tar.md"), "# tar\n");
+        write_page(&root.path().join("pages/common/tar.md"), "# tar\n");
+        let ls_en = root.path().join("pages/linux/ls.md");
+        write_page(&ls_en, "# ls\n");
+        let cache = test_cache(root.path(), &["de", "en"]);
+
+        // Pages are edited in the language they are shown in
+        let tar_edited = root.path().join("data/overlay/de/common/tar.md");
+        let ls_edited = root.path().join("data/overlay/en/linux/ls.md");
+        assert_eq!(cache.prepare_page_to_edit("tar").unwrap(), tar_edited);
+        assert_eq!(cache.prepare_page_to_edit("ls").unwrap(), ls_edited);
+        assert_eq!(cache.list_page_paths().unwrap(),
+                   vec![("ls".to_string(), ls_edited.clone()), ("tar".to_string(), tar_edited.clone())]);
+        assert_eq!(cache.page_language(&tar_edited), "de");
+        assert_eq!(cache.page_language(&ls_edited), "en");
+
+        // Resetting covers the edited pages of all languages
+        assert_eq!(cache.reset_page("tar").unwrap(), 1);
+        assert_eq!(cache.find_page("tar", false), Some(root.path().join("pages.de/common/tar.md")));
+        write_page(&tar_edited, "# tar\n");
+        assert_eq!(cache.reset_all_pages().unwrap(), 2);
+        assert_eq!(cache.find_page("ls", false), Some(ls_en));
+    }
+}
@@ -0,0 +1,127 @@
+//! Ordering of page names for listings.
+
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Take the run of ASCII digits at the start of `chars`.
+fn take_number(chars: &mut Peekable<Chars>) -> String {
+    let mut number = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        number.push(c);
+        let _ = chars.next();
+    }
+    number
+}
+
+/// Return the base letters of a lowercase Latin letter with diacritics,
+/// like `a` for `ä`, or `None` for other characters.
+fn base_letters(c: char) -> Option<&'static str> {
+    let base = match c {
+        'à'..='å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ð' | 'ď' | 'đ' => "d",
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ĳ' => "ij",
+        'ĵ' => "j",
+        'ķ' | 'ĸ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' | 'ŉ' | 'ŋ' => "n",
+        'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ß' => "ss",
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ſ' => "s",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'þ' => "th",
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    };
+    Some(base)
+}
+
+/// Return `name` in lowercase, with Latin letters with diacritics replaced
+/// by their base letters and combining diacritical marks removed.
+fn fold(name: &str) -> String {
+    let mut folded = String::with_capacity(name.len());
+    for c in name.chars().flat_map(char::to_lowercase) {
+        match base_letters(c) {
+            Some(base) => folded.push_str(base),
+            None if ('\u{300}'..='\u{36f}').contains(&c) => {},
+            None => folded.push(c),
+        }
+    }
+    folded
+}
+
+/// Compare two names in natural order: case and diacritics of Latin
+/// letters are ignored (so that `ä` sorts with `a`), and runs of digits
+/// are compared by their numeric value, so that `x2` sorts before `x10`.
+/// Names that are equal by these rules (e.g. that only differ in case,
+/// diacritics or leading zeros) are ordered byte-wise.
+///
+/// This is not a full locale collation: letters of other scripts are
+/// compared by code point, and language specific rules (like `ä` after
+/// `z` in Swedish) are not applied.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (a_folded, b_folded) = (fold(a), fold(b));
+    let (mut a_chars, mut b_chars) = (a_folded.chars().peekable(), b_folded.chars().peekable());
+    loop {
+        let ordering = match (a_chars.peek().cloned(), b_chars.peek().cloned()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (x, y) = (take_number(&mut a_chars), take_number(&mut b_chars));
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+            },
+            (Some(x), Some(y)) => {
+                let _ = (a_chars.next(), b_chars.next());
+                x.cmp(&y)
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+
+    use super::natural_cmp;
+
+    #[test]
+    fn test_natural_cmp_numbers() {
+        assert_eq!(natural_cmp("python2", "python10"), Ordering::Less);
+        assert_eq!(natural_cmp("x10", "x010"), Ordering::Greater);
+        assert_eq!(natural_cmp("7z", "zstd"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_natural_cmp_case() {
+        let mut names = vec!["zip", "Xz", "ä", "apt", "B", "a"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, vec!["a", "ä", "apt", "B", "Xz", "zip"]);
+        assert_eq!(natural_cmp("Tar", "tar"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_natural_cmp_diacritics() {
+        let mut names = vec!["ed", "zsh", "Éclair", "eb", "Łódź", "lz4", "straße", "strasse", "stra\u{300}ss"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, vec!["eb", "Éclair", "ed", "Łódź", "lz4", "stra\u{300}ss", "strasse", "straße", "zsh"]);
+    }
+}
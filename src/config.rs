@@ -0,0 +1,112 @@
+//! Configuration read from environment variables.
+
+use std::env;
+use std::path::PathBuf;
+
+/// Expand `${NAME}` references to environment variables in `value`.
+///
+/// A `$` that is not followed by `{` is kept as is.
+pub fn expand_vars(value: &str) -> Result<String, String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let reference = &rest[start + 2..];
+        let end = try!(reference.find('}').ok_or_else(|| {
+            format!("Unterminated variable reference in '{}'", value)
+        }));
+        let name = &reference[..end];
+        match env::var(name) {
+            Ok(var) => expanded.push_str(&var),
+            Err(_) => return Err(format!("Environment variable ${} is not set", name)),
+        }
+        rest = &reference[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Return the value of the environment variable `name` with references to
+/// other environment variables expanded, or `None` if it is not set.
+pub fn var(name: &str) -> Result<Option<String>, String> {
+    match env::var(name) {
+        Ok(value) => expand_vars(&value).map(Some)
+                                        .map_err(|msg| format!("Invalid ${}: {}", name, msg)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Split a list of languages like `pt_BR:pt:en`, separated by colons or
+/// commas, removing duplicates.
+fn parse_languages(value: &str) -> Vec<String> {
+    let mut languages: Vec<String> = vec![];
+    for language in value.split(|c| c == ':' || c == ',').map(|language| language.trim()) {
+        if !language.is_empty() && !languages.iter().any(|l| l == language) {
+            languages.push(language.to_string());
+        }
+    }
+    languages
+}
+
+/// Return the languages to show pages in, in order of preference: those
+/// given with `--language`, or else those in `$TLDR_LANGUAGES`. An empty
+/// list means English.
+pub fn languages(arg: Option<&str>) -> Result<Vec<String>, String> {
+    match arg {
+        Some(value) => Ok(parse_languages(value)),
+        None => var("TLDR_LANGUAGES").map(|value| value.map(|value| parse_languages(&value)).unwrap_or_default()),
+    }
+}
+
+/// Return the directory where tealdeer keeps its own data.
+///
+/// This is `$XDG_DATA_HOME/tealdeer`, falling back to
+/// `$HOME/.local/share/tealdeer`.
+pub fn data_dir() -> Option<PathBuf> {
+    match env::var("XDG_DATA_HOME") {
+        Ok(ref dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("tealdeer")),
+        _ => env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share").join("tealdeer")),
+    }
+}
+
+/// Return whether the boolean environment variable `name` is set to a
+/// true value (`1`, `true` or `yes`).
+pub fn flag(name: &str) -> bool {
+    match env::var(name) {
+        Ok(value) => {
+            let value = value.to_lowercase();
+            value == "1" || value == "true" || value == "yes"
+        },
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+
+    use super::{expand_vars, parse_languages};
+
+    #[test]
+    fn test_expand_vars() {
+        env::set_var("TEALDEER_TEST_EXPAND", "/home/deer");
+        assert_eq!(expand_vars("${TEALDEER_TEST_EXPAND}/pages").unwrap(), "/home/deer/pages");
+        assert_eq!(expand_vars("a${TEALDEER_TEST_EXPAND}b${TEALDEER_TEST_EXPAND}").unwrap(),
+                   "a/home/deerb/home/deer");
+        assert_eq!(expand_vars("no $references here").unwrap(), "no $references here");
+    }
+
+    #[test]
+    fn test_expand_vars_errors() {
+        env::remove_var("TEALDEER_TEST_UNSET");
+        assert!(expand_vars("${TEALDEER_TEST_UNSET}/pages").is_err());
+        assert!(expand_vars("${HOME").is_err());
+    }
+
+    #[test]
+    fn test_parse_languages() {
+        assert_eq!(parse_languages("pt_BR:pt:en"), vec!["pt_BR", "pt", "en"]);
+        assert_eq!(parse_languages(" de, en ,de"), vec!["de", "en"]);
+        assert!(parse_languages("").is_empty());
+    }
+}
@@ -0,0 +1,211 @@
+//! A daemon that answers page lookups over a Unix socket.
+//!
+//! A client sends a single tab separated request line
+//! `<page dir>\t<os>\t<languages>\t<ignore case>\t<name>`, where the
+//! languages are separated by colons, and receives either `FOUND <path>`
+//! followed by the page content or `MISSING`. Requests for
+//! another page directory than the one of the daemon are answered with
+//! `UNSUPPORTED`, so that the client looks up the page itself.
+
+#[cfg(unix)]
+mod imp {
+    use std::collections::HashMap;
+    use std::fs::{self, File};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::Shutdown;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    use cache::Cache;
+    use config;
+    use types::OsType;
+
+    /// How long a client waits for the daemon before giving up.
+    const TIMEOUT_MS: u64 = 500;
+
+    /// How long the daemon waits for a client to send its request. This is
+    /// well below `TIMEOUT_MS`, so that a stalled client does not make the
+    /// clients after it give up.
+    const REQUEST_TIMEOUT_MS: u64 = 100;
+
+    /// Return the path to the daemon socket.
+    fn socket_path() -> Option<PathBuf> {
+        config::data_dir().map(|dir| dir.join("daemon.sock"))
+    }
+
+    /// Build the request line for a page lookup.
+    fn request_line(os: OsType, languages: &[String], ignore_case: bool, name: &str) -> String {
+        let page_dir = config::var("TLDR_PAGE_DIR").ok().and_then(|dir| dir).unwrap_or_default();
+        let languages = if languages.is_empty() { "en".to_string() } else { languages.join(":") };
+        format!("{}\t{}\t{}\t{}\t{}\n",
+                page_dir, format!("{:?}", os).to_lowercase(), languages,
+                if ignore_case { 1 } else { 0 }, name)
+    }
+
+    /// The content of a page held in memory, with the modification time it
+    /// was read at.
+    struct HotPage {
+        modified: Option<SystemTime>,
+        content: String,
+    }
+
+    /// Return the modification time of the file at `path`.
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+    }
+
+    /// Answer a single request line.
+    ///
+    /// The page is looked up on every request, so that newly edited pages
+    /// and pages of a preferred language or platform that appeared since the
+    /// last request are found. Only reading it is saved.
+    fn answer(url: &str, request: &str, pages: &mut HashMap<PathBuf, HotPage>) -> String {
+        let fields = request.trim_end_matches('\n').split('\t').collect::<Vec<_>>();
+        if fields.len() != 5 {
+            return "UNSUPPORTED\n".into();
+        }
+        let own_page_dir = config::var("TLDR_PAGE_DIR").ok().and_then(|dir| dir).unwrap_or_default();
+        let os = match fields[1].parse::<OsType>() {
+            Ok(os) if fields[0] == own_page_dir => os,
+            _ => return "UNSUPPORTED\n".into(),
+        };
+
+        let languages = fields[2].split(':').map(|language| language.to_string()).collect();
+        let cache = Cache::new(url, os, languages);
+        let path = match cache.find_page(fields[4], fields[3] == "1") {
+            Some(path) => path,
+            None => return "MISSING\n".into(),
+        };
+
+        // Serve the page from memory, unless it changed on disk
+        let modified = modified(&path);
+        if let Some(page) = pages.get(&path) {
+            if modified.is_some() && page.modified == modified {
+                return format!("FOUND {}\n{}", path.display(), page.content);
+            }
+        }
+
+        let mut content = String::new();
+        if let Err(e) = File::open(&path).and_then(|mut file| file.read_to_string(&mut content)) {
+            warn!("Could not read {}: {}", path.display(), e);
+            let _ = pages.remove(&path);
+            return "MISSING\n".into();
+        }
+        let response = format!("FOUND {}\n{}", path.display(), content);
+        let _ = pages.insert(path, HotPage {
+            modified: modified,
+            content: content,
+        });
+        response
+    }
+
+    /// Listen on the daemon socket and answer lookups until killed.
+    pub fn serve(url: &str) -> Result<(), String> {
+        let path = try!(socket_path().ok_or_else(|| "Could not determine data directory".to_string()));
+        if let Some(dir) = path.parent() {
+            try!(fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e)));
+        }
+
+        // Remove the socket of a previous daemon, unless it is still running
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                return Err(format!("A daemon is already listening on {}", path.display()));
+            }
+            try!(fs::remove_file(&path).map_err(|e| format!("Could not remove {}: {}", path.display(), e)));
+        }
+        let listener = try!(UnixListener::bind(&path).map_err(|e| format!("Could not listen on {}: {}", path.display(), e)));
+        println!("Listening on {}", path.display());
+
+        let mut pages = HashMap::new();
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("Could not accept connection: {}", e);
+                    continue;
+                },
+            };
+            let timeout = Some(Duration::from_millis(REQUEST_TIMEOUT_MS));
+            let _ = stream.set_read_timeout(timeout);
+            let _ = stream.set_write_timeout(timeout);
+            let mut request = String::new();
+            let read = stream.try_clone().and_then(|reader| BufReader::new(reader).read_line(&mut request));
+            if let Err(e) = read {
+                warn!("Could not read request: {}", e);
+                continue;
+            }
+            let response = answer(url, &request, &mut pages);
+            if let Err(e) = stream.write_all(response.as_bytes()) {
+                warn!("Could not send response: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Return whether a daemon is listening on the socket.
+    pub fn is_running() -> bool {
+        socket_path().map(|path| UnixStream::connect(path).is_ok()).unwrap_or(false)
+    }
+
+    /// Look up a page through the daemon. Return the path and content of
+    /// the page, or `None` if the daemon is not running or could not find it.
+    pub fn lookup(os: OsType, languages: &[String], ignore_case: bool, name: &str) -> Option<(PathBuf, String)> {
+        let path = match socket_path() {
+            Some(ref path) if path.exists() => path.clone(),
+            _ => return None,
+        };
+        let mut stream = match UnixStream::connect(path) {
+            Ok(stream) => stream,
+            Err(e) => {
+                debug!("Could not connect to daemon: {}", e);
+                return None;
+            },
+        };
+        let timeout = Some(Duration::from_millis(TIMEOUT_MS));
+        let _ = stream.set_read_timeout(timeout);
+        let _ = stream.set_write_timeout(timeout);
+
+        let request = request_line(os, languages, ignore_case, name);
+        let mut response = String::new();
+        let result = stream.write_all(request.as_bytes())
+                           .and_then(|_| stream.shutdown(Shutdown::Write))
+                           .and_then(|_| stream.read_to_string(&mut response));
+        if let Err(e) = result {
+            debug!("Could not query daemon: {}", e);
+            return None;
+        }
+
+        let mut parts = response.splitn(2, '\n');
+        match (parts.next(), parts.next()) {
+            (Some(status), Some(content)) if status.starts_with("FOUND ") => {
+                Some((PathBuf::from(&status["FOUND ".len()..]), content.to_string()))
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::path::PathBuf;
+
+    use types::OsType;
+
+    /// Daemon mode needs Unix sockets.
+    pub fn serve(_url: &str) -> Result<(), String> {
+        Err("Daemon mode is only supported on Unix.".into())
+    }
+
+    /// Without daemon support, no daemon can be running.
+    pub fn is_running() -> bool {
+        false
+    }
+
+    /// Without daemon support, pages are always looked up directly.
+    pub fn lookup(_os: OsType, _languages: &[String], _ignore_case: bool, _name: &str) -> Option<(PathBuf, String)> {
+        None
+    }
+}
+
+pub use self::imp::{serve, is_running, lookup};
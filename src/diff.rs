@@ -0,0 +1,136 @@
+//! A minimal line based unified diff.
+
+use ansi_term::{Colour, Style};
+
+/// Number of unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+/// A single line of a diff.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Compute the line diff between `old` and `new` using the longest common
+/// subsequence of their lines.
+pub fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffLine<'a>> {
+    // lcs[i][j] is the LCS length of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    lines.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+    lines
+}
+
+/// Format the difference between `old` and `new` as a unified diff,
+/// coloured if `coloured` is set.
+///
+/// Return `None` if both texts are equal.
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str, coloured: bool) -> Option<String> {
+    let style = |style: Style| if coloured { style } else { Style::new() };
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+    let lines = diff_lines(&old_lines, &new_lines);
+    if lines.iter().all(|line| matches!(*line, DiffLine::Same(_))) {
+        return None;
+    }
+
+    let mut out = String::new();
+    out.push_str(&format!("{}\n", style(Colour::Red.bold()).paint(format!("--- {}", old_name))));
+    out.push_str(&format!("{}\n", style(Colour::Green.bold()).paint(format!("+++ {}", new_name))));
+
+    // Find the hunks: ranges of diff lines that contain changes plus context
+    let changed = lines.iter()
+                       .enumerate()
+                       .filter(|&(_, line)| !matches!(*line, DiffLine::Same(_)))
+                       .map(|(i, _)| i)
+                       .collect::<Vec<_>>();
+    let mut hunks: Vec<(usize, usize)> = vec![];
+    for &i in &changed {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some(hunk) if start <= hunk.1 => hunk.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    for (start, end) in hunks {
+        // Determine line numbers of the hunk in both files
+        let count = |range: &[DiffLine], old: bool| {
+            range.iter().filter(|line| match **line {
+                DiffLine::Same(_) => true,
+                DiffLine::Removed(_) => old,
+                DiffLine::Added(_) => !old,
+            }).count()
+        };
+        let old_start = count(&lines[..start], true) + 1;
+        let new_start = count(&lines[..start], false) + 1;
+        let header = format!("@@ -{},{} +{},{} @@",
+                             old_start, count(&lines[start..end], true),
+                             new_start, count(&lines[start..end], false));
+        out.push_str(&format!("{}\n", style(Colour::Cyan.normal()).paint(header)));
+        for line in &lines[start..end] {
+            let formatted = match *line {
+                DiffLine::Same(text) => format!(" {}", text),
+                DiffLine::Removed(text) => style(Colour::Red.normal()).paint(format!("-{}", text)).to_string(),
+                DiffLine::Added(text) => style(Colour::Green.normal()).paint(format!("+{}", text)).to_string(),
+            };
+            out.push_str(&formatted);
+            out.push('\n');
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{diff_lines, unified_diff};
+    use super::DiffLine::{Same, Removed, Added};
+
+    #[test]
+    fn test_diff_lines() {
+        let old = ["a", "b", "c", "d"];
+        let new = ["a", "c", "d", "e"];
+        assert_eq!(diff_lines(&old, &new),
+                   vec![Same("a"), Removed("b"), Same("c"), Same("d"), Added("e")]);
+    }
+
+    #[test]
+    fn test_diff_lines_replace() {
+        assert_eq!(diff_lines(&["a", "b"], &["a", "x"]), vec![Same("a"), Removed("b"), Added("x")]);
+        assert_eq!(diff_lines(&[], &["a"]), vec![Added("a")]);
+    }
+
+    #[test]
+    fn test_unified_diff_equal() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "old", "new", false), None);
+        assert!(unified_diff("a\nb\n", "a\nc\n", "old", "new", false).is_some());
+    }
+}
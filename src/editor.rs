@@ -0,0 +1,92 @@
+//! Opening pages in the user's editor.
+
+use std::env;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::Command;
+
+/// Editors to try if neither `$VISUAL` nor `$EDITOR` is set.
+#[cfg(windows)]
+const DEFAULT_EDITORS: [&'static str; 1] = ["notepad"];
+#[cfg(not(windows))]
+const DEFAULT_EDITORS: [&'static str; 2] = ["nano", "vi"];
+
+/// Split an editor command like `code --wait` into its words. Words may be
+/// quoted with single or double quotes, so that paths with spaces work.
+pub fn split_command(command: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                in_word = true;
+            },
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(word.clone());
+                    word.clear();
+                    in_word = false;
+                }
+            },
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            },
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// Return the editor commands to try, in order: `$VISUAL`, `$EDITOR` and
+/// the platform defaults if neither is set.
+fn editor_commands() -> Vec<Vec<String>> {
+    let configured = ["VISUAL", "EDITOR"].iter()
+                                         .filter_map(|var| env::var(var).ok())
+                                         .map(|command| split_command(&command))
+                                         .filter(|words| !words.is_empty())
+                                         .collect::<Vec<_>>();
+    if configured.is_empty() {
+        DEFAULT_EDITORS.iter().map(|editor| vec![editor.to_string()]).collect()
+    } else {
+        configured
+    }
+}
+
+/// Open a file in the editor and wait until the editor exits.
+///
+/// Editors that cannot be found are skipped, so e.g. a stale `$VISUAL`
+/// falls back to `$EDITOR`.
+pub fn edit(path: &Path) -> Result<(), String> {
+    for words in editor_commands() {
+        let status = Command::new(&words[0]).args(&words[1..]).arg(path).status();
+        match status {
+            Ok(ref status) if status.success() => return Ok(()),
+            Ok(status) => return Err(format!("Editor {} exited with {}", words[0], status)),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => debug!("Editor {} not found", words[0]),
+            Err(e) => return Err(format!("Could not run editor {}: {}", words[0], e)),
+        }
+    }
+    Err("Could not find an editor. Set $VISUAL or $EDITOR.".to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::split_command;
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(split_command("vim"), vec!["vim"]);
+        assert_eq!(split_command("  code --wait "), vec!["code", "--wait"]);
+        assert_eq!(split_command("\"/opt/My Editor/edit\" -w"), vec!["/opt/My Editor/edit", "-w"]);
+        assert_eq!(split_command("emacs -nw ''"), vec!["emacs", "-nw", ""]);
+        assert!(split_command(" ").is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use curl::ErrCode;
+#[cfg(feature = "network")] use curl::ErrCode;
 
 #[derive(Debug)]
 pub enum TealdeerError {
@@ -6,6 +6,7 @@ pub enum TealdeerError {
     UpdateError(String),
 }
 
+#[cfg(feature = "network")]
 impl From<ErrCode> for TealdeerError {
     fn from(err: ErrCode) -> TealdeerError {
         TealdeerError::UpdateError(err.to_string())
@@ -0,0 +1,216 @@
+//! A C API for looking up and rendering pages from programs written in
+//! other languages.
+//!
+//! Strings returned by this module are owned by tealdeer and must be
+//! released with `tealdeer_free`, not with the C `free`.
+
+use std::env;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use formatter::{print_lines, print_lines_accessible};
+use html;
+use lookup::PageDirs;
+use style::Styles;
+use tokenizer::Tokenizer;
+use types::OsType;
+
+/// Render a page as plain text, without colours.
+pub const TEALDEER_FORMAT_TEXT: c_int = 0;
+/// Render a page for an ANSI terminal, with the default colours.
+pub const TEALDEER_FORMAT_ANSI: c_int = 1;
+/// Render a page for screen readers.
+pub const TEALDEER_FORMAT_ACCESSIBLE: c_int = 2;
+/// Render a page as an HTML document.
+pub const TEALDEER_FORMAT_HTML: c_int = 3;
+
+/// Render the page `markdown` (in the tldr page format) named `name` in
+/// one of the `TEALDEER_FORMAT_*` formats. The name is only used for the
+/// title of HTML documents.
+///
+/// Return the rendered page, or a null pointer if an argument is null or
+/// the format is unknown.
+///
+/// # Safety
+///
+/// `markdown` and `name` must be null or point to NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn tealdeer_render_to_string(markdown: *const c_char,
+                                                   name: *const c_char,
+                                                   format: c_int) -> *mut c_char {
+    if markdown.is_null() || name.is_null() {
+        return ptr::null_mut();
+    }
+    let markdown = CStr::from_ptr(markdown).to_bytes();
+    let name = CStr::from_ptr(name).to_string_lossy();
+
+    let mut tokenizer = Tokenizer::new(markdown);
+    let mut out = vec![];
+    let result = match format {
+        TEALDEER_FORMAT_TEXT => print_lines(&mut tokenizer, &Styles::plain(), &mut out),
+        TEALDEER_FORMAT_ANSI => print_lines(&mut tokenizer, &Styles::default(), &mut out),
+        TEALDEER_FORMAT_ACCESSIBLE => print_lines_accessible(&mut tokenizer, &mut out),
+        TEALDEER_FORMAT_HTML => {
+            out = html::render_page(&mut tokenizer, &name, "en", None).into_bytes();
+            Ok(())
+        },
+        _ => return ptr::null_mut(),
+    };
+    match result.ok().and_then(|_| CString::new(out).ok()) {
+        Some(rendered) => rendered.into_raw(),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Return the path of the page `name` for `platform` in `language` below
+/// `page_dir`, as a string.
+fn find_page(page_dir: &Path, name: &str, platform: &str, language: &str) -> Option<String> {
+    let os = match platform.parse::<OsType>() {
+        Ok(os) => os,
+        Err(_) => return None,
+    };
+    PageDirs::new(page_dir.to_path_buf(), os, vec![language.to_string()])
+        .find_page(name, false)
+        .and_then(|path| path.to_str().map(|path| path.to_string()))
+}
+
+/// Look up the page `name` for `platform` (`linux`, `macos`, `sunos` or
+/// `other`) in `language` (`en` for the untranslated pages) in the page
+/// directory given by `$TLDR_PAGE_DIR`. As with `tldr`, platform specific
+/// pages take precedence over common ones. Locally edited pages are not
+/// considered.
+///
+/// Return the path of the page, or a null pointer if there is no such page,
+/// `$TLDR_PAGE_DIR` is not set, or an argument is null or not valid UTF-8.
+///
+/// # Safety
+///
+/// `name`, `platform` and `language` must be null or point to
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn tealdeer_find_page(name: *const c_char,
+                                            platform: *const c_char,
+                                            language: *const c_char) -> *mut c_char {
+    if name.is_null() || platform.is_null() || language.is_null() {
+        return ptr::null_mut();
+    }
+    let arguments = (CStr::from_ptr(name).to_str(), CStr::from_ptr(platform).to_str(), CStr::from_ptr(language).to_str());
+    let (name, platform, language) = match arguments {
+        (Ok(name), Ok(platform), Ok(language)) => (name, platform, language),
+        _ => return ptr::null_mut(),
+    };
+    let page_dir = match env::var_os("TLDR_PAGE_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => return ptr::null_mut(),
+    };
+    match find_page(&page_dir, name, platform, language).and_then(|path| CString::new(path).ok()) {
+        Some(path) => path.into_raw(),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Release a string returned by `tealdeer_find_page` or
+/// `tealdeer_render_to_string`. Null pointers are ignored.
+///
+/// # Safety
+///
+/// `string` must be null or a string returned by this library that has
+/// not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn tealdeer_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::ffi::{CStr, CString};
+    use std::fs::{self, File};
+    use std::ptr;
+
+    use tempdir::TempDir;
+
+    use super::{tealdeer_find_page, tealdeer_free, tealdeer_render_to_string,
+                TEALDEER_FORMAT_HTML, TEALDEER_FORMAT_TEXT};
+
+    /// Call `tealdeer_find_page` and return the path it found.
+    fn find(name: &[u8], platform: &str, language: &str) -> Option<String> {
+        let name = CString::new(name).unwrap();
+        let platform = CString::new(platform).unwrap();
+        let language = CString::new(language).unwrap();
+        unsafe {
+            let path = tealdeer_find_page(name.as_ptr(), platform.as_ptr(), language.as_ptr());
+            if path.is_null() {
+                return None;
+            }
+            let string = CStr::from_ptr(path).to_string_lossy().into_owned();
+            tealdeer_free(path);
+            Some(string)
+        }
+    }
+
+    fn render(markdown: &str, format: i32) -> Option<String> {
+        let markdown = CString::new(markdown).unwrap();
+        let name = CString::new("tar").unwrap();
+        unsafe {
+            let rendered = tealdeer_render_to_string(markdown.as_ptr(), name.as_ptr(), format);
+            if rendered.is_null() {
+                return None;
+            }
+            let string = CStr::from_ptr(rendered).to_string_lossy().into_owned();
+            tealdeer_free(rendered);
+            Some(string)
+        }
+    }
+
+    #[test]
+    fn test_render_to_string() {
+        let page = "# tar\n\n> Archiving utility.\n\n- Extract an archive:\n\n`tar xf {{file}}`\n";
+        assert_eq!(render(page, TEALDEER_FORMAT_TEXT).unwrap(),
+                   "  Archiving utility.\n\n  - Extract an archive:\n    tar xf file\n\n");
+        assert!(render(page, TEALDEER_FORMAT_HTML).unwrap().contains("<var>file</var>"));
+        assert_eq!(render(page, 42), None);
+    }
+
+    #[test]
+    fn test_find_page() {
+        let root = TempDir::new("tealdeer-ffi").unwrap();
+        let pages = root.path().join("pages");
+        for dir in &["common", "linux", "macos"] {
+            fs::create_dir_all(pages.join(dir)).unwrap();
+        }
+        let _ = File::create(pages.join("common/tar.md")).unwrap();
+        let _ = File::create(pages.join("linux/tar.md")).unwrap();
+        let _ = File::create(pages.join("common/git.md")).unwrap();
+        // The only test that uses the environment, so that tests can run
+        // in parallel
+        env::set_var("TLDR_PAGE_DIR", &pages);
+
+        let path = |relative: &str| Some(pages.join(relative).display().to_string());
+        assert_eq!(find(b"tar", "linux", "en"), path("linux/tar.md"));
+        assert_eq!(find(b"tar", "macos", "en"), path("common/tar.md"));
+        assert_eq!(find(b"git", "linux", "en"), path("common/git.md"));
+        assert_eq!(find(b"missing", "linux", "en"), None);
+        assert_eq!(find(b"tar", "linux", "de"), None);
+        assert_eq!(find(b"tar", "windows", "en"), None);
+        assert_eq!(find(b"../pages/common/tar", "linux", "en"), path("linux/tar.md"));
+        assert_eq!(find(b"t\xffr", "linux", "en"), None);
+    }
+
+    #[test]
+    fn test_null_arguments() {
+        let tar = CString::new("tar").unwrap();
+        let linux = CString::new("linux").unwrap();
+        unsafe {
+            assert!(tealdeer_render_to_string(ptr::null(), ptr::null(), TEALDEER_FORMAT_TEXT).is_null());
+            assert!(tealdeer_find_page(ptr::null(), linux.as_ptr(), tar.as_ptr()).is_null());
+            assert!(tealdeer_find_page(tar.as_ptr(), ptr::null(), tar.as_ptr()).is_null());
+            assert!(tealdeer_find_page(tar.as_ptr(), linux.as_ptr(), ptr::null()).is_null());
+            tealdeer_free(ptr::null_mut());
+        }
+    }
+}
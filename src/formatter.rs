@@ -1,38 +1,307 @@
 //! Functions related to formatting and printing lines from a `Tokenizer`.
 
-use std::io::BufRead;
+use std::io::{self, BufRead, Write};
 
-use ansi_term::{Colour, ANSIStrings};
+use ansi_term::ANSIStrings;
 
+use highlight::highlight_command;
+use style::Styles;
 use tokenizer::Tokenizer;
-use types::LineType;
+use types::{LineType, is_table_separator};
+
+/// The output formats pages can be printed in.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Format {
+    /// Indented and styled output for terminals.
+    Ansi,
+    /// Unstyled output with explicit labels, for screen readers.
+    Accessible,
+    /// An HTML document (see `html::render_page`).
+    Html,
+    /// The page as it is, in its markdown format.
+    Markdown,
+}
 
 /// Provide formatting for {{ curly braces }} in ExampleCode lines
-fn format_braces(text: &str) -> String {
+fn format_braces(text: &str, styles: &Styles) -> String {
     let parts = text.split("{{").flat_map(|s| s.split("}}"))
                     .enumerate()
                     .map(|(i, v)| {
                         if i % 2 == 0 {
-                            Colour::Cyan.paint(v)
+                            styles.example_code.paint(v)
                         } else {
-                            Colour::Cyan.underline().paint(v)
+                            styles.example_variable.paint(v)
                         }
                     })
                     .collect::<Vec<_>>();
     ANSIStrings(&parts).to_string()
 }
 
-/// Print a token stream to an ANSI terminal.
-pub fn print_lines<R>(tokenizer: &mut Tokenizer<R>) where R: BufRead {
+/// Prepend the configured bullet to an example description or list item.
+fn with_bullet(text: &str, styles: &Styles) -> String {
+    if styles.bullet.is_empty() {
+        text.to_string()
+    } else {
+        format!("{} {}", styles.bullet, text)
+    }
+}
+
+/// Format an ExampleCode line with the configured command prefix, and
+/// with shell syntax highlighting if enabled.
+fn format_command(text: &str, styles: &Styles) -> String {
+    let command = if styles.highlight { highlight_command(text, styles) } else { format_braces(text, styles) };
+    if styles.command_prefix.is_empty() {
+        command
+    } else {
+        format!("{}{}", styles.example_code.paint(format!("{} ", styles.command_prefix)), command)
+    }
+}
+
+/// Split an ExampleCode line into the command with the {{ curly braces }}
+/// removed and the list of placeholders.
+fn split_placeholders(text: &str) -> (String, Vec<String>) {
+    let mut command = String::new();
+    let mut placeholders = vec![];
+    for (i, part) in text.split("{{").flat_map(|s| s.split("}}")).enumerate() {
+        command.push_str(part);
+        if i % 2 == 1 && !part.is_empty() {
+            placeholders.push(part.to_string());
+        }
+    }
+    (command, placeholders)
+}
+
+/// Align the cells of a table in columns. Separator rows become lines of
+/// dashes under each column.
+fn format_table(rows: &[Vec<String>]) -> Vec<String> {
+    let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for row in rows.iter().filter(|row| !is_table_separator(row)) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    rows.iter().map(|row| {
+        let line = widths.iter().enumerate().map(|(i, &width)| {
+            if is_table_separator(row) {
+                "-".repeat(width)
+            } else {
+                let cell = row.get(i).map(|cell| &cell[..]).unwrap_or("");
+                format!("{}{}", cell, " ".repeat(width - cell.chars().count()))
+            }
+        }).collect::<Vec<_>>().join("  ");
+        line.trim_end().to_string()
+    }).collect()
+}
+
+/// Write the buffered rows of a table to `out` and clear them.
+fn print_table<W: Write>(rows: &mut Vec<Vec<String>>, styles: &Styles, out: &mut W) -> io::Result<()> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+    try!(writeln!(out));
+    for line in format_table(rows) {
+        try!(writeln!(out, "  {}", styles.description.paint(line)));
+    }
+    rows.clear();
+    writeln!(out)
+}
+
+/// Write a token stream to `out`, formatted for an ANSI terminal.
+pub fn print_lines<R, W>(tokenizer: &mut Tokenizer<R>, styles: &Styles, out: &mut W) -> io::Result<()>
+        where R: BufRead, W: Write {
+    // Tables are buffered until their last row, to align the columns
+    let mut table = vec![];
     while let Some(token) = tokenizer.next_token() {
         match token {
-            LineType::Empty => print!(""),
+            LineType::TableRow(_) | LineType::Empty => {},
+            _ => try!(print_table(&mut table, styles, out)),
+        }
+        match token {
+            LineType::Empty => {},
             LineType::Title(_) => debug!("Ignoring title"),
-            LineType::Description(text) => println!("  {}\n", text),
-            LineType::ExampleText(text) => println!("  {}", Colour::Green.paint(format!("- {}", text))),
-            LineType::ExampleCode(text) => println!("    {}", &format_braces(&text)),
+            LineType::Description(text) => try!(writeln!(out, "  {}\n", styles.description.paint(text))),
+            LineType::ExampleText(text) => try!(writeln!(out, "  {}", styles.example_text.paint(with_bullet(&text, styles)))),
+            LineType::ExampleCode(text) => try!(writeln!(out, "    {}", &format_command(&text, styles))),
+            LineType::Heading(text) => {
+                let underline = "-".repeat(text.chars().count());
+                try!(writeln!(out, "\n  {}\n  {}\n", styles.description.paint(text), styles.description.paint(underline)));
+            },
+            LineType::ListItem(depth, text) => {
+                try!(writeln!(out, "  {}{}", "  ".repeat(depth), styles.example_text.paint(with_bullet(&text, styles))));
+            },
+            LineType::TableRow(cells) => table.push(cells),
+            LineType::Other(text) => debug!("Unknown line type: {:?}", text),
+        }
+    }
+    try!(print_table(&mut table, styles, out));
+    writeln!(out)
+}
+
+/// Write a token stream to `out` for screen readers: without colours or
+/// indentation, with labelled sections and with placeholders spelled out
+/// separately.
+pub fn print_lines_accessible<R, W>(tokenizer: &mut Tokenizer<R>, out: &mut W) -> io::Result<()>
+        where R: BufRead, W: Write {
+    let mut description_started = false;
+    let mut example = 0;
+    while let Some(token) = tokenizer.next_token() {
+        match token {
+            LineType::Empty => {},
+            LineType::Title(text) => try!(writeln!(out, "Command: {}", text)),
+            LineType::Description(text) => {
+                if description_started {
+                    try!(writeln!(out, "{}", text));
+                } else {
+                    try!(writeln!(out, "Description: {}", text));
+                    description_started = true;
+                }
+            },
+            LineType::ExampleText(text) => {
+                example += 1;
+                try!(writeln!(out, "\nExample {}: {}", example, text));
+            },
+            LineType::ExampleCode(text) => {
+                let (command, placeholders) = split_placeholders(&text);
+                try!(writeln!(out, "Run: {}", command));
+                if !placeholders.is_empty() {
+                    try!(writeln!(out, "Placeholders: {}", placeholders.join(", ")));
+                }
+            },
+            LineType::Heading(text) => try!(writeln!(out, "\nSection: {}", text)),
+            LineType::ListItem(1, text) => try!(writeln!(out, "Item: {}", text)),
+            LineType::ListItem(depth, text) => try!(writeln!(out, "Item, level {}: {}", depth, text)),
+            LineType::TableRow(ref cells) if is_table_separator(cells) => {},
+            LineType::TableRow(cells) => try!(writeln!(out, "Row: {}", cells.join("; "))),
             LineType::Other(text) => debug!("Unknown line type: {:?}", text),
         }
     }
-    println!("");
+    writeln!(out)
+}
+
+/// Write a compact summary of a page to `out`: the command and the first
+/// description line on one line, followed by the first `examples` examples,
+/// one per line. This is meant for `command_not_found_handle` hooks.
+pub fn print_compact<R, W>(tokenizer: &mut Tokenizer<R>, styles: &Styles, examples: usize, out: &mut W) -> io::Result<()>
+        where R: BufRead, W: Write {
+    let mut title = None;
+    let mut summary = None;
+    let mut text = String::new();
+    let mut lines = vec![];
+    while let Some(token) = tokenizer.next_token() {
+        match token {
+            LineType::Title(name) => title = Some(name),
+            LineType::Description(description) => if summary.is_none() { summary = Some(description) },
+            LineType::ExampleText(description) => text = description,
+            LineType::ExampleCode(code) => {
+                let text = text.trim_end_matches(':');
+                lines.push(format!("    {}  {}", format_command(&code, styles),
+                                   styles.example_text.paint(format!("# {}", text))));
+                if lines.len() == examples {
+                    break;
+                }
+            },
+            _ => {},
+        }
+    }
+    if let Some(title) = title {
+        match summary {
+            Some(summary) => try!(writeln!(out, "{}: {}", title, styles.description.paint(summary))),
+            None => try!(writeln!(out, "{}", title)),
+        }
+    }
+    for line in lines {
+        try!(writeln!(out, "{}", line));
+    }
+    Ok(())
+}
+
+/// Arrange names in columns like `ls`, filling each column from top to
+/// bottom, so that the lines do not exceed `width` characters.
+pub fn format_columns(names: &[String], width: usize) -> String {
+    const SPACING: usize = 2;
+    let max_len = names.iter().map(|name| name.chars().count()).max().unwrap_or(0);
+    let column_width = max_len + SPACING;
+    let columns = ((width + SPACING) / column_width).max(1);
+    let rows = (names.len() + columns - 1) / columns;
+
+    let mut out = String::new();
+    for row in 0..rows {
+        let mut line = String::new();
+        for name in (0..columns).filter_map(|column| names.get(column * rows + row)) {
+            line.push_str(&format!("{:width$}", name, width = column_width));
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::{split_placeholders, format_columns, format_table, print_compact, print_lines};
+    use style::Styles;
+    use tokenizer::Tokenizer;
+
+    #[test]
+    fn test_split_placeholders() {
+        assert_eq!(split_placeholders("tar cf {{target.tar}} {{file}}"),
+                   ("tar cf target.tar file".to_string(), vec!["target.tar".to_string(), "file".to_string()]));
+        assert_eq!(split_placeholders("ls -la"), ("ls -la".to_string(), vec![]));
+    }
+
+    #[test]
+    fn test_format_columns() {
+        let names = ["ip", "ls", "tar", "xz", "zip"].iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(format_columns(&names, 80), "ip   ls   tar  xz   zip\n");
+        assert_eq!(format_columns(&names, 13), "ip   tar  zip\nls   xz\n");
+        assert_eq!(format_columns(&names, 1), "ip\nls\ntar\nxz\nzip\n");
+        assert_eq!(format_columns(&[], 80), "");
+    }
+
+    #[test]
+    fn test_format_table() {
+        let rows = vec![
+            vec!["Flag".to_string(), "Meaning".to_string()],
+            vec!["---".to_string(), "---".to_string()],
+            vec!["-x".to_string(), "Extract".to_string()],
+            vec!["--create".to_string()],
+        ];
+        assert_eq!(format_table(&rows), vec!["Flag      Meaning", "--------  -------", "-x        Extract", "--create"]);
+    }
+
+    #[test]
+    fn test_print_lines_extended() {
+        let page = "# deploy\n\n> Deploy the app.\n\n## Staging\n\n- Deploy:\n  - to one region\n    - slowly\n\n\
+                    `deploy {{env}}`\n\n| Env | Region |\n|---|---|\n| staging | eu |\n";
+        let mut out = vec![];
+        print_lines(&mut Tokenizer::new(page.as_bytes()), &Styles::plain(), &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "  Deploy the app.\n\n\n  Staging\n  -------\n\n  - Deploy:\n    - to one region\n      - slowly\n\
+                    \x20   deploy env\n\n  Env      Region\n  -------  ------\n  staging  eu\n\n\n");
+    }
+
+    #[test]
+    fn test_print_lines_glyphs() {
+        let page = "# tar\n\n- Create:\n  - from files\n\n`tar cf {{file}}`\n";
+        let styles = Styles { bullet: "\u{203a}".into(), command_prefix: "$".into(), ..Styles::plain() };
+        let mut out = vec![];
+        print_lines(&mut Tokenizer::new(page.as_bytes()), &styles, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "  \u{203a} Create:\n    \u{203a} from files\n    $ tar cf file\n\n");
+
+        let styles = Styles { bullet: "".into(), ..Styles::plain() };
+        let mut out = vec![];
+        print_lines(&mut Tokenizer::new(page.as_bytes()), &styles, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "  Create:\n    from files\n    tar cf file\n\n");
+    }
+
+    #[test]
+    fn test_print_compact() {
+        let page = "# tar\n\n> Archiving utility.\n> More information.\n\n- Create:\n\n`tar cf {{file}}`\n\n\
+                    - Extract:\n\n`tar xf {{file}}`\n\n- List:\n\n`tar tf {{file}}`\n";
+        let mut out = vec![];
+        print_compact(&mut Tokenizer::new(page.as_bytes()), &Styles::plain(), 2, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "tar: Archiving utility.\n    tar cf file  # Create\n    tar xf file  # Extract\n");
+    }
 }
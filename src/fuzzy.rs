@@ -0,0 +1,82 @@
+//! Finding the page that was meant by a misspelled command name.
+
+use std::cmp;
+
+/// The largest edit distance at which a page is still offered as a match.
+const MAX_DISTANCE: usize = 2;
+
+/// How much closer the match must be than the second closest candidate.
+const MIN_MARGIN: usize = 2;
+
+/// Return the Levenshtein distance between two names, counted in chars.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..b.len() + 1).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+    for (i, x) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, &y) in b.iter().enumerate() {
+            let substitution = previous[j] + if x == y { 0 } else { 1 };
+            current[j + 1] = cmp::min(substitution, cmp::min(previous[j + 1], current[j]) + 1);
+        }
+        previous.clone_from(&current);
+    }
+    previous[b.len()]
+}
+
+/// Return the candidate that is clearly closest to `name`: it must be
+/// within a small edit distance (relative to the length of `name`), and
+/// every other candidate must be at least `MIN_MARGIN` edits further away.
+pub fn best_match<'a>(name: &str, candidates: &'a [String]) -> Option<&'a str> {
+    let max_distance = cmp::min(MAX_DISTANCE, cmp::max(1, name.chars().count() / 3));
+    let mut best: Option<(usize, &str)> = None;
+    let mut second_distance = None;
+    for candidate in candidates {
+        let distance = levenshtein(name, candidate);
+        if distance == 0 {
+            continue;
+        }
+        match best {
+            Some((best_distance, _)) if distance >= best_distance => {
+                second_distance = Some(second_distance.map_or(distance, |second| cmp::min(second, distance)));
+            },
+            _ => {
+                second_distance = best.map(|(best_distance, _)| best_distance);
+                best = Some((distance, candidate));
+            },
+        }
+    }
+    match (best, second_distance) {
+        (Some((distance, _)), Some(second)) if second < distance + MIN_MARGIN => None,
+        (Some((distance, candidate)), _) if distance <= max_distance => Some(candidate),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{best_match, levenshtein};
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("tar", "tar"), 0);
+        assert_eq!(levenshtein("tarr", "tar"), 1);
+        assert_eq!(levenshtein("gti", "git"), 2);
+        assert_eq!(levenshtein("", "ls"), 2);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_best_match() {
+        let pages = vec!["tar".to_string(), "top".to_string(), "git".to_string(), "gpg".to_string()];
+        assert_eq!(best_match("tarr", &pages), Some("tar"));
+        assert_eq!(best_match("gitt", &pages), Some("git"));
+        // Equally close to `git` and `gpg`
+        assert_eq!(best_match("gig", &pages), None);
+        // Only one edit closer to `tar` than to `tap`
+        let close_pages = vec!["tar".to_string(), "tap".to_string()];
+        assert_eq!(best_match("tarr", &close_pages), None);
+        // Too far from everything
+        assert_eq!(best_match("xyz", &pages), None);
+    }
+}
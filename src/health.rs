@@ -0,0 +1,88 @@
+//! Diagnostics for `--health`.
+
+use std::fs::{self, File};
+use std::io::Read;
+
+use cache::Cache;
+use config;
+use error::TealdeerError::{UpdateError, CacheError};
+use style::{ColourSupport, Styles};
+use types::OsType;
+
+/// The outcome of a single diagnostic check.
+#[derive(Debug)]
+pub struct Check {
+    /// What was checked.
+    pub name: &'static str,
+    /// A description of the result, as `Err` if the check failed.
+    pub result: Result<String, String>,
+}
+
+/// Check that the configuration environment variables parse.
+fn check_config() -> Result<String, String> {
+    let page_dir = try!(config::var("TLDR_PAGE_DIR"));
+    if let Some(platform) = try!(config::var("TLDR_DEFAULT_PLATFORM")) {
+        let _ = try!(platform.parse::<OsType>().map_err(|msg| format!("Invalid $TLDR_DEFAULT_PLATFORM: {}", msg)));
+    }
+    let _ = try!(config::languages(None));
+    let _ = try!(Styles::from_env());
+    match page_dir {
+        Some(page_dir) => Ok(format!("$TLDR_PAGE_DIR is {}", page_dir)),
+        None => Err("$TLDR_PAGE_DIR isn't set".into()),
+    }
+}
+
+/// Check that the data directory can be determined and written to.
+fn check_data_dir() -> Result<String, String> {
+    let dir = try!(config::data_dir().ok_or_else(|| "Could not determine data directory".to_string()));
+    try!(fs::create_dir_all(&dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e)));
+    let probe = dir.join(".health");
+    try!(File::create(&probe).map_err(|e| format!("{} is not writable: {}", dir.display(), e)));
+    let _ = fs::remove_file(&probe);
+    Ok(format!("{} is writable", dir.display()))
+}
+
+/// Check that every listed page can be read and is the one a lookup finds.
+fn check_pages(cache: &Cache) -> Result<String, String> {
+    let pages = try!(cache.list_page_paths().map_err(|e| match e {
+        UpdateError(msg) | CacheError(msg) => msg,
+    }));
+    if pages.is_empty() {
+        return Err("No pages found".into());
+    }
+    let mut problems = vec![];
+    for (name, path) in &pages {
+        let mut content = String::new();
+        if let Err(e) = File::open(path).and_then(|mut file| file.read_to_string(&mut content)) {
+            problems.push(format!("{} is not readable: {}", path.display(), e));
+        } else if cache.find_page(name, false).as_ref() != Some(path) {
+            problems.push(format!("{} does not resolve to {}", name, path.display()));
+        }
+    }
+    if problems.is_empty() {
+        Ok(format!("{} pages", pages.len()))
+    } else {
+        Err(problems.join("; "))
+    }
+}
+
+/// Check that the terminal can show the configured colours.
+fn check_colours() -> Result<String, String> {
+    let support = ColourSupport::detect();
+    match (support, config::var("TLDR_COLORS")) {
+        (ColourSupport::NoColour, Ok(Some(_))) => {
+            Err("$TLDR_COLORS is set, but $TERM does not support colours".into())
+        },
+        (support, _) => Ok(format!("{:?}", support)),
+    }
+}
+
+/// Run all checks.
+pub fn run(cache: &Cache) -> Vec<Check> {
+    vec![
+        Check { name: "config", result: check_config() },
+        Check { name: "data directory", result: check_data_dir() },
+        Check { name: "pages", result: check_pages(cache) },
+        Check { name: "colours", result: check_colours() },
+    ]
+}
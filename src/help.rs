@@ -0,0 +1,211 @@
+//! Building a page from the `--help` output of a command, for commands
+//! without a page.
+
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long the command may take to print its help.
+const TIMEOUT_MS: u64 = 2000;
+
+/// The maximum number of options that are turned into examples.
+const MAX_OPTIONS: usize = 12;
+
+/// Run `command --help` and return what it printed, or `None` if it could
+/// not be run, failed or did not finish in time.
+///
+/// Only commands from `$PATH` are run, never paths to files.
+pub fn run_help(command: &str) -> Option<String> {
+    if command.contains('/') || command.contains('\\') {
+        return None;
+    }
+    let mut child = match Command::new(command).arg("--help")
+                                               .stdin(Stdio::null())
+                                               .stdout(Stdio::piped())
+                                               .stderr(Stdio::piped())
+                                               .spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            debug!("Could not run {} --help: {}", command, e);
+            return None;
+        },
+    };
+
+    // Read the output in the background, so that the command does not
+    // block on a full pipe. Some commands print their help to stderr.
+    // The readers are never joined: a process started by the command may
+    // keep the pipes open long after the command itself has exited.
+    let readers: Vec<Option<Box<dyn Read + Send>>> = vec![
+        child.stdout.take().map(|s| -> Box<dyn Read + Send> { Box::new(s) }),
+        child.stderr.take().map(|s| -> Box<dyn Read + Send> { Box::new(s) }),
+    ];
+    let (sender, receiver) = mpsc::channel();
+    let mut pending = 0;
+    for (index, reader) in readers.into_iter().enumerate() {
+        let mut reader = match reader {
+            Some(reader) => reader,
+            None => continue,
+        };
+        let sender = sender.clone();
+        pending += 1;
+        let _ = thread::spawn(move || {
+            let mut output = String::new();
+            let _ = reader.read_to_string(&mut output);
+            let _ = sender.send((index, output));
+        });
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(TIMEOUT_MS);
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            _ => {
+                debug!("{} --help did not finish in time", command);
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            },
+        }
+    };
+    if !status.success() {
+        return None;
+    }
+    let mut outputs = vec![String::new(), String::new()];
+    while pending > 0 {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(remaining) {
+            Ok((index, output)) => outputs[index] = output,
+            Err(_) => {
+                debug!("The output of {} --help was not closed in time", command);
+                return None;
+            },
+        }
+        pending -= 1;
+    }
+    outputs.into_iter().find(|output| !output.trim().is_empty())
+}
+
+/// Turn `<arg>` and `=ARG` in an option or usage line into placeholders.
+fn mark_placeholders(text: &str) -> String {
+    let mut marked = text.replace('<', "{{").replace('>', "}}");
+    if let Some(pos) = marked.find('=') {
+        let arg = marked[pos + 1..].split(|c: char| c == ' ' || c == ',' || c == ']').next().unwrap_or("").to_string();
+        if !arg.is_empty() && !arg.starts_with("{{") && arg.chars().all(|c| c.is_uppercase() || c == '_') {
+            marked = format!("{}={{{{{}}}}}{}", &marked[..pos], arg, &marked[pos + 1 + arg.len()..]);
+        }
+    }
+    marked
+}
+
+/// Split an option line like `  -v, --verbose   Print more` into the option
+/// and its description (which may be empty).
+fn split_option(line: &str) -> Option<(String, String)> {
+    let trimmed = line.trim();
+    if !line.starts_with(char::is_whitespace) || !trimmed.starts_with('-') {
+        return None;
+    }
+    match trimmed.find("  ").or_else(|| trimmed.find('\t')) {
+        Some(pos) => Some((trimmed[..pos].trim().to_string(), trimmed[pos..].trim().to_string())),
+        None => Some((trimmed.to_string(), String::new())),
+    }
+}
+
+/// Convert the `--help` output of `name` into a page: usage lines and
+/// options become examples, the first other line the description.
+pub fn help_to_page(name: &str, help: &str) -> String {
+    let mut description = None;
+    let mut examples = vec![];
+    let mut options = 0;
+    let mut in_usage = false;
+    let mut lines = help.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            in_usage = false;
+            continue;
+        }
+
+        // Usage lines, possibly continued on the following indented lines
+        let lowercase = trimmed.to_lowercase();
+        if lowercase.starts_with("usage:") {
+            in_usage = true;
+            let usage = trimmed[6..].trim();
+            if !usage.is_empty() {
+                examples.push(("Usage".to_string(), mark_placeholders(usage)));
+            }
+            continue;
+        }
+        if in_usage && line.starts_with(char::is_whitespace) && !trimmed.starts_with('-') {
+            examples.push(("Usage".to_string(), mark_placeholders(trimmed)));
+            continue;
+        }
+        in_usage = false;
+
+        if let Some((option, mut text)) = split_option(line) {
+            if options == MAX_OPTIONS {
+                continue;
+            }
+            // The description may be on the next line
+            if text.is_empty() {
+                let next = lines.peek().map(|next| next.to_string()).unwrap_or_default();
+                if next.starts_with(char::is_whitespace) && !next.trim().is_empty() && !next.trim().starts_with('-') {
+                    text = next.trim().to_string();
+                    let _ = lines.next();
+                }
+            }
+            if text.is_empty() {
+                text = format!("Use {}", option);
+            }
+            examples.push((text, format!("{} {}", name, mark_placeholders(&option))));
+            options += 1;
+        } else if description.is_none() && !trimmed.ends_with(':') {
+            description = Some(trimmed.to_string());
+        }
+    }
+
+    let mut page = format!("# {}\n\n", name);
+    if let Some(description) = description {
+        page.push_str(&format!("> {}\n", description));
+    }
+    page.push_str(&format!("> Generated from `{} --help`, there is no page for this command.\n", name));
+    for (text, code) in examples {
+        page.push_str(&format!("\n- {}\n\n`{}`\n", text.trim_end_matches('.'), code));
+    }
+    page
+}
+
+#[cfg(test)]
+mod test {
+    use super::{help_to_page, mark_placeholders, split_option};
+
+    #[test]
+    fn test_mark_placeholders() {
+        assert_eq!(mark_placeholders("foo [options] <file>"), "foo [options] {{file}}");
+        assert_eq!(mark_placeholders("--output=FILE"), "--output={{FILE}}");
+        assert_eq!(mark_placeholders("--color=auto"), "--color=auto");
+    }
+
+    #[test]
+    fn test_split_option() {
+        assert_eq!(split_option("  -v, --verbose    Print more"),
+                   Some(("-v, --verbose".to_string(), "Print more".to_string())));
+        assert_eq!(split_option("  --quiet"), Some(("--quiet".to_string(), String::new())));
+        assert_eq!(split_option("-v is not indented"), None);
+        assert_eq!(split_option("  some text"), None);
+    }
+
+    #[test]
+    fn test_help_to_page() {
+        let help = "Usage: foo [options] <file>\n\nFrobnicate files.\n\nOptions:\n  \
+                    -v, --verbose   Print more.\n  --output=FILE\n        Write to FILE\n";
+        assert_eq!(help_to_page("foo", help),
+                   "# foo\n\n> Frobnicate files.\n> Generated from `foo --help`, there is no page for this command.\n\
+                    \n- Usage\n\n`foo [options] {{file}}`\n\
+                    \n- Print more\n\n`foo -v, --verbose`\n\
+                    \n- Write to FILE\n\n`foo --output={{FILE}}`\n");
+    }
+}
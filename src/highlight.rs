@@ -0,0 +1,163 @@
+//! Shell syntax highlighting of example commands.
+//!
+//! This is not a shell parser, only a lexer good enough for the one-line
+//! commands of pages: it knows about words, quotes, `{{placeholders}}` and
+//! the operators that separate or redirect commands.
+
+use ansi_term::{ANSIStrings, Style};
+
+use style::Styles;
+
+/// The syntactic role of a piece of an example command.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum Kind {
+    /// The command itself, arguments and whitespace.
+    Plain,
+    /// A word right after the command, like `commit` in `git commit`.
+    Subcommand,
+    /// A word starting with `-`.
+    Flag,
+    /// A quoted string, including the quotes.
+    Quoted,
+    /// Pipes, `&&`, `;`, redirections, `$(` and parentheses.
+    Operator,
+    /// A `{{placeholder}}`, without the braces.
+    Placeholder,
+}
+
+/// Return whether `c` separates or redirects commands.
+fn is_operator(c: char) -> bool {
+    "|&;<>()".contains(c)
+}
+
+/// Return whether `word` looks like a subcommand rather than an argument.
+fn is_subcommand(word: &str) -> bool {
+    word.chars().next().map_or(false, |c| c.is_alphabetic())
+        && word.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Split an example command into pieces of the same kind.
+fn spans(text: &str) -> Vec<(Kind, String)> {
+    let mut spans: Vec<(Kind, String)> = vec![];
+    let mut push = |kind: Kind, s: &str| {
+        match spans.last_mut() {
+            Some(&mut (last, ref mut content)) if last == kind => return content.push_str(s),
+            _ => {},
+        }
+        spans.push((kind, s.to_string()));
+    };
+
+    // The number of words in the current command, and the kind of the
+    // word that is being read
+    let mut words = 0;
+    let mut word = None;
+    let mut quote = None;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("{{") {
+            if let Some(end) = rest.find("}}") {
+                if word.is_none() {
+                    words += 1;
+                    word = Some(Kind::Plain);
+                }
+                push(Kind::Placeholder, &rest[2..end]);
+                rest = &rest[end + 2..];
+                continue;
+            }
+        }
+        let len = c.len_utf8();
+        let kind = match quote {
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+                Kind::Quoted
+            },
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                if word.is_none() {
+                    words += 1;
+                    word = Some(Kind::Plain);
+                }
+                Kind::Quoted
+            },
+            None if c.is_whitespace() => {
+                word = None;
+                Kind::Plain
+            },
+            None if is_operator(c) || (c == '$' && rest[len..].starts_with('(')) => {
+                word = None;
+                // Redirections are followed by a file name, everything else
+                // by a new command
+                if c != '<' && c != '>' {
+                    words = 0;
+                }
+                Kind::Operator
+            },
+            None => match word {
+                Some(kind) => kind,
+                None => {
+                    words += 1;
+                    let end = rest.find(|c: char| c.is_whitespace() || is_operator(c) || c == '"' || c == '\'')
+                                  .unwrap_or(rest.len());
+                    let kind = if c == '-' {
+                        Kind::Flag
+                    } else if words == 2 && is_subcommand(&rest[..end]) && !rest[..end].contains("{{") {
+                        Kind::Subcommand
+                    } else {
+                        Kind::Plain
+                    };
+                    word = Some(kind);
+                    kind
+                },
+            },
+        };
+        push(kind, &rest[..len]);
+        rest = &rest[len..];
+    }
+    spans
+}
+
+/// Format an example command with shell syntax highlighting.
+pub fn highlight_command(text: &str, styles: &Styles) -> String {
+    let parts = spans(text).into_iter().map(|(kind, content)| {
+        let style: Style = match kind {
+            Kind::Plain => styles.example_code,
+            Kind::Subcommand => styles.example_subcommand,
+            Kind::Flag => styles.example_flag,
+            Kind::Quoted => styles.example_string,
+            Kind::Operator => styles.example_operator,
+            Kind::Placeholder => styles.example_variable,
+        };
+        style.paint(content)
+    }).collect::<Vec<_>>();
+    ANSIStrings(&parts).to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{spans, Kind};
+    use super::Kind::*;
+
+    fn expected(pieces: &[(Kind, &str)]) -> Vec<(Kind, String)> {
+        pieces.iter().map(|&(kind, content)| (kind, content.to_string())).collect()
+    }
+
+    #[test]
+    fn test_spans_subcommand_and_flags() {
+        assert_eq!(spans("git commit -m \"{{message}}\""),
+                   expected(&[(Plain, "git "), (Subcommand, "commit"), (Plain, " "), (Flag, "-m"), (Plain, " "),
+                              (Quoted, "\""), (Placeholder, "message"), (Quoted, "\"")]));
+        assert_eq!(spans("tar --file={{archive.tar}}"),
+                   expected(&[(Plain, "tar "), (Flag, "--file="), (Placeholder, "archive.tar")]));
+    }
+
+    #[test]
+    fn test_spans_pipelines() {
+        assert_eq!(spans("ps aux | grep 'foo bar' > {{file}} && echo $(date)"),
+                   expected(&[(Plain, "ps "), (Subcommand, "aux"), (Plain, " "), (Operator, "|"), (Plain, " grep "),
+                              (Quoted, "'foo bar'"), (Plain, " "), (Operator, ">"), (Plain, " "), (Placeholder, "file"),
+                              (Plain, " "), (Operator, "&&"), (Plain, " echo "), (Operator, "$("), (Plain, "date"),
+                              (Operator, ")")]));
+    }
+}
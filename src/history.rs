@@ -0,0 +1,100 @@
+//! Finding the most used commands in a shell history file.
+
+use std::collections::BTreeMap;
+
+/// Commands that run the command given as their argument, with their
+/// options that take a separate argument (like `-u root`).
+const WRAPPERS: [(&'static str, &'static [&'static str]); 5] = [
+    ("sudo", &["-u", "-g", "-C", "-D", "-h", "-p", "-r", "-t", "-U", "--user", "--group", "--chdir"]),
+    ("doas", &["-u", "-C"]),
+    ("env", &["-u", "-C", "--unset", "--chdir"]),
+    ("nohup", &[]),
+    ("time", &["-f", "-o", "--format", "--output"]),
+];
+
+/// Return the command line of a history entry, or `None` for lines that
+/// do not contain one. Plain (bash), extended zsh (`: <time>:0;<command>`)
+/// and fish (`- cmd: <command>`) history lines are supported.
+fn command_line(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if let Some(command) = line.strip_prefix("- cmd:") {
+        return Some(command.trim());
+    }
+    if line.starts_with(": ") {
+        return line.find(';').map(|pos| line[pos + 1..].trim());
+    }
+    // Skip bash timestamps and the other fields of fish entries
+    if line.is_empty() || line.starts_with('#') || line.starts_with("when:") || line.starts_with("paths:") {
+        return None;
+    }
+    Some(line)
+}
+
+/// Return the name of the command run by a command line, skipping variable
+/// assignments and wrappers like `sudo` with their options.
+fn command_name(command_line: &str) -> Option<String> {
+    let mut words = command_line.split_whitespace();
+    let mut wrapper_options: &[&str] = &[];
+    while let Some(word) = words.next() {
+        if word.starts_with('-') {
+            if wrapper_options.contains(&word) {
+                let _ = words.next();
+            }
+            continue;
+        }
+        if word.contains('=') {
+            continue;
+        }
+        if let Some(&(_, options)) = WRAPPERS.iter().find(|&&(wrapper, _)| wrapper == word) {
+            wrapper_options = options;
+            continue;
+        }
+        let name = word.rsplit('/').next().unwrap_or(word);
+        return if name.is_empty() { None } else { Some(name.to_string()) };
+    }
+    None
+}
+
+/// Return the `limit` most used commands in the history `content`, most
+/// used first.
+pub fn most_used(content: &str, limit: usize) -> Vec<String> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for name in content.lines().filter_map(command_line).filter_map(command_name) {
+        *counts.entry(name).or_insert(0) += 1;
+    }
+    let mut commands = counts.into_iter().collect::<Vec<_>>();
+    commands.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    commands.into_iter().take(limit).map(|(name, _)| name).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{command_name, most_used};
+
+    #[test]
+    fn test_command_name() {
+        assert_eq!(command_name("tar xf foo.tar"), Some("tar".into()));
+        assert_eq!(command_name("LANG=C sudo -E /usr/bin/ip a"), Some("ip".into()));
+        assert_eq!(command_name("FOO=bar"), None);
+    }
+
+    #[test]
+    fn test_command_name_wrapper_options() {
+        assert_eq!(command_name("sudo -u root ls /root"), Some("ls".into()));
+        assert_eq!(command_name("sudo -uroot -g wheel -i ls"), Some("ls".into()));
+        assert_eq!(command_name("env FOO=1 BAR=2 cmd --flag"), Some("cmd".into()));
+        assert_eq!(command_name("env -u HOME -C /tmp bash"), Some("bash".into()));
+        assert_eq!(command_name("time -f %e nohup make"), Some("make".into()));
+        assert_eq!(command_name("doas -u admin"), None);
+    }
+
+    #[test]
+    fn test_most_used() {
+        let bash = "#1612345678\nls -la\ngit status\nls\n";
+        assert_eq!(most_used(bash, 10), vec!["ls", "git"]);
+        let zsh = ": 1612345678:0;git log\n: 1612345679:0;git diff\n: 1612345680:0;tar xf a\n";
+        assert_eq!(most_used(zsh, 1), vec!["git"]);
+        let fish = "- cmd: tar xf a\n  when: 1612345678\n- cmd: ls\n  when: 1612345679\n- cmd: tar cf b\n";
+        assert_eq!(most_used(fish, 10), vec!["tar", "ls"]);
+    }
+}
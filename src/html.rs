@@ -0,0 +1,214 @@
+//! Rendering pages as HTML.
+
+use std::io::BufRead;
+
+use tokenizer::Tokenizer;
+use types::{LineType, is_table_separator};
+
+/// Escape the characters that have a special meaning in HTML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Percent-encode a segment of a link path, so that characters like `#`,
+/// `?`, `%` and spaces in page names stay part of the file name.
+pub fn encode_path_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Format an ExampleCode line, marking up {{ curly braces }} as variables.
+fn format_code(text: &str) -> String {
+    text.split("{{").flat_map(|s| s.split("}}"))
+        .enumerate()
+        .map(|(i, part)| if i % 2 == 0 { escape(part) } else { format!("<var>{}</var>", escape(part)) })
+        .collect()
+}
+
+/// Return the start of an HTML document.
+fn header(title: &str, language: &str) -> String {
+    format!("<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{}</title>\n</head>\n<body>\n", escape(language), escape(title))
+}
+
+/// Render the rows of a table. If the second row is a separator, the
+/// first one is the header.
+fn render_table(rows: &[Vec<String>]) -> String {
+    let has_header = rows.len() > 1 && is_table_separator(&rows[1]);
+    let mut table = String::from("<table>\n");
+    for (i, row) in rows.iter().enumerate().filter(|&(_, row)| !is_table_separator(row)) {
+        let tag = if has_header && i == 0 { "th" } else { "td" };
+        table.push_str("<tr>");
+        for cell in row {
+            table.push_str(&format!("<{}>{}</{}>", tag, escape(cell), tag));
+        }
+        table.push_str("</tr>\n");
+    }
+    table.push_str("</table>\n");
+    table
+}
+
+/// Close the nested lists that are open up to `depth`.
+fn close_lists(body: &mut String, open: &mut usize, depth: usize) {
+    while *open > depth {
+        body.push_str("</li>\n</ul>\n");
+        *open -= 1;
+    }
+}
+
+/// Render a page as an HTML document. If `index_href` is given, the page
+/// links to the index of all pages there.
+pub fn render_page<R: BufRead>(tokenizer: &mut Tokenizer<R>, name: &str, language: &str,
+                               index_href: Option<&str>) -> String {
+    let mut body = String::new();
+    let mut in_examples = false;
+    let mut open_lists = 0;
+    let mut table = vec![];
+    while let Some(token) = tokenizer.next_token() {
+        match token {
+            LineType::Empty | LineType::ListItem(..) | LineType::TableRow(_) => {},
+            _ => close_lists(&mut body, &mut open_lists, 0),
+        }
+        match token {
+            LineType::Empty | LineType::TableRow(_) => {},
+            _ if !table.is_empty() => {
+                body.push_str(&render_table(&table));
+                table.clear();
+            },
+            _ => {},
+        }
+        match token {
+            LineType::Empty => {},
+            LineType::Title(text) => body.push_str(&format!("<h1>{}</h1>\n", escape(&text))),
+            LineType::Description(text) => body.push_str(&format!("<p>{}</p>\n", escape(&text))),
+            LineType::ExampleText(text) => {
+                if in_examples {
+                    body.push_str("</li>\n");
+                } else {
+                    body.push_str("<ul>\n");
+                    in_examples = true;
+                }
+                body.push_str(&format!("<li><p>{}</p>\n", escape(&text)));
+            },
+            LineType::ExampleCode(text) => {
+                body.push_str(&format!("<pre><code>{}</code></pre>\n", format_code(&text)));
+            },
+            LineType::Heading(text) => body.push_str(&format!("<h2>{}</h2>\n", escape(&text))),
+            LineType::ListItem(depth, text) => {
+                if depth > open_lists {
+                    body.push_str(&"<ul>\n".repeat(depth - open_lists));
+                } else {
+                    close_lists(&mut body, &mut open_lists, depth);
+                    body.push_str("</li>\n");
+                }
+                open_lists = depth;
+                body.push_str(&format!("<li>{}", escape(&text)));
+            },
+            LineType::TableRow(cells) => table.push(cells),
+            LineType::Other(text) => debug!("Unknown line type: {:?}", text),
+        }
+    }
+    close_lists(&mut body, &mut open_lists, 0);
+    if !table.is_empty() {
+        body.push_str(&render_table(&table));
+    }
+    if in_examples {
+        body.push_str("</li>\n</ul>\n");
+    }
+    format!("{}{}{}</body>\n</html>\n", header(name, language), nav(index_href), body)
+}
+
+/// Return the link to the index at `index_href`, if any.
+fn nav(index_href: Option<&str>) -> String {
+    index_href.map(|href| format!("<nav><a href=\"{}\">All pages</a></nav>\n", escape(href)))
+              .unwrap_or_default()
+}
+
+/// Render an index titled `title`. `entries` contains the name, link and
+/// summary of each page or section. If `index_href` is given, the index
+/// links to the index above it there.
+pub fn render_index(title: &str, entries: &[(String, String, String)], language: &str,
+                    index_href: Option<&str>) -> String {
+    let mut body = format!("<h1>{}</h1>\n<ul>\n", escape(title));
+    for (name, href, summary) in entries {
+        body.push_str(&format!("<li><a href=\"{}\">{}</a> \u{2014} {}</li>\n",
+                               escape(href), escape(name), escape(summary)));
+    }
+    body.push_str("</ul>\n");
+    format!("{}{}{}</body>\n</html>\n", header(title, language), nav(index_href), body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{encode_path_segment, escape, format_code, render_index, render_page};
+    use tokenizer::Tokenizer;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a <b> & \"c\""), "a &lt;b&gt; &amp; &quot;c&quot;");
+    }
+
+    #[test]
+    fn test_encode_path_segment() {
+        assert_eq!(encode_path_segment("git-commit_2.x~"), "git-commit_2.x~");
+        assert_eq!(encode_path_segment("c# 100%?"), "c%23%20100%25%3F");
+        assert_eq!(encode_path_segment("a/b&\u{e4}"), "a%2Fb%26%C3%A4");
+    }
+
+    #[test]
+    fn test_format_code() {
+        assert_eq!(format_code("tar cf {{target.tar}} {{<file>}}"),
+                   "tar cf <var>target.tar</var> <var>&lt;file&gt;</var>");
+    }
+
+    #[test]
+    fn test_render_page() {
+        let page = "# tar\n\n> Archiving utility.\n\n- Create:\n\n`tar cf {{file}}`\n";
+        let html = render_page(&mut Tokenizer::new(page.as_bytes()), "tar", "en", Some("../index.html"));
+        assert!(html.contains("<html lang=\"en\">"));
+        assert!(html.contains("<title>tar</title>"));
+        assert!(html.contains("<nav><a href=\"../index.html\">All pages</a></nav>"));
+        assert!(html.contains("<h1>tar</h1>\n<p>Archiving utility.</p>\n<ul>\n<li><p>Create:</p>\n\
+                               <pre><code>tar cf <var>file</var></code></pre>\n</li>\n</ul>\n"));
+    }
+
+    #[test]
+    fn test_render_index() {
+        let entries = vec![("tar".to_string(), "tar.html".to_string(), "Archiving <utility>.".to_string())];
+        let html = render_index("tldr pages: common (de)", &entries, "de", Some("../../index.html"));
+        assert!(html.contains("<html lang=\"de\">"));
+        assert!(html.contains("<nav><a href=\"../../index.html\">All pages</a></nav>\n<h1>tldr pages: common (de)</h1>"));
+        assert!(html.contains("<li><a href=\"tar.html\">tar</a> \u{2014} Archiving &lt;utility&gt;.</li>"));
+        assert!(!render_index("tldr pages", &entries, "en", None).contains("<nav>"));
+    }
+
+    #[test]
+    fn test_render_page_extended() {
+        let page = "# deploy\n\n## Staging\n\n- Deploy:\n  - one\n    - two\n  - three\n\n`deploy`\n\n\
+                    | Env | Region |\n|---|---|\n| a&b | eu |\n";
+        let html = render_page(&mut Tokenizer::new(page.as_bytes()), "deploy", "en", None);
+        assert!(!html.contains("<nav>"));
+        assert!(html.contains("<h2>Staging</h2>\n<ul>\n<li><p>Deploy:</p>\n\
+                               <ul>\n<li>one<ul>\n<li>two</li>\n</ul>\n</li>\n<li>three</li>\n</ul>\n\
+                               <pre><code>deploy</code></pre>\n\
+                               <table>\n<tr><th>Env</th><th>Region</th></tr>\n<tr><td>a&amp;b</td><td>eu</td></tr>\n</table>\n\
+                               </li>\n</ul>\n"));
+    }
+}
@@ -0,0 +1,40 @@
+//! The page format of [tldr](https://github.com/tldr-pages/tldr) in Rust:
+//! tokenizing pages and rendering them for terminals, screen readers and
+//! HTML, and (with the `lookup` feature) finding them in page directories.
+//
+// Copyright (c) 2015-2016 tealdeer developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be
+// copied, modified, or distributed except according to those terms.
+//
+// This library does not touch the filesystem, the network or other
+// processes, so that it also builds for `wasm32-unknown-unknown` (with
+// `--no-default-features`). The only exception is the page lookup behind
+// the `lookup` feature. Everything else lives in the `tldr` binary.
+
+#![deny(missing_docs, missing_debug_implementations,
+        unsafe_code,
+        unused_import_braces, unused_qualifications)]
+#![warn(trivial_casts, trivial_numeric_casts,
+        missing_copy_implementations,
+        unused_extern_crates, unused_results)]
+
+#[macro_use] extern crate log;
+extern crate ansi_term;
+#[cfg(feature = "cli")] extern crate rustc_serialize;
+#[cfg(all(test, feature = "ffi"))] extern crate tempdir;
+
+pub mod types;
+pub mod tokenizer;
+pub mod formatter;
+pub mod highlight;
+pub mod html;
+pub mod style;
+#[cfg(feature = "lookup")]
+pub mod lookup;
+#[cfg(feature = "ffi")]
+#[allow(unsafe_code)]
+pub mod ffi;
@@ -0,0 +1,274 @@
+//! Looking up pages in a page directory.
+//!
+//! Unlike the rest of the library, this reads the filesystem. It is only
+//! built with the `lookup` feature, which the `cli` and `ffi` features
+//! enable.
+
+use std::path::{Path, PathBuf};
+
+use types::OsType;
+
+/// The directories holding the pages of one language.
+#[derive(Debug)]
+pub struct LanguageDirs {
+    /// The language of the pages, like `en` or `pt_BR`.
+    pub language: String,
+    /// The directory with the platform directories of the pages.
+    pub pages: PathBuf,
+    /// The directory with locally edited pages of this language, if any.
+    pub overlay: Option<PathBuf>,
+}
+
+impl LanguageDirs {
+    /// Return the directories to search for pages, in order of precedence:
+    /// the overlay directory (if any) and the page directory.
+    pub fn roots(&self) -> Vec<&Path> {
+        self.overlay.iter().map(|overlay| overlay.as_path()).chain(Some(self.pages.as_path())).collect()
+    }
+
+    /// Return the path of a page relative to the overlay directory, if
+    /// the page at `path` is a locally edited page of this language.
+    ///
+    /// Only paths directly in a platform directory count, like the pages in
+    /// the page directory.
+    pub fn relative_overlay_path<'a>(&self, path: &'a Path) -> Option<&'a Path> {
+        self.overlay.as_ref()
+            .and_then(|overlay| path.strip_prefix(overlay).ok())
+            .and_then(|relative| if relative.components().count() == 2 { Some(relative) } else { None })
+    }
+}
+
+/// The page directories of a platform and a list of languages.
+#[derive(Debug)]
+pub struct PageDirs {
+    page_dir: PathBuf,
+    overlay_dir: Option<PathBuf>,
+    os: OsType,
+    languages: Vec<String>,
+}
+
+impl PageDirs {
+    /// Return the directories of the pages in `page_dir` for `os`, in
+    /// `languages` in order of preference. Pages of a later language are
+    /// only used if there is no page in an earlier one. `en` stands for the
+    /// untranslated pages, which are also used if `languages` is empty.
+    pub fn new(page_dir: PathBuf, os: OsType, languages: Vec<String>) -> PageDirs {
+        let languages = if languages.is_empty() { vec!["en".to_string()] } else { languages };
+        PageDirs {
+            page_dir: page_dir,
+            overlay_dir: None,
+            os: os,
+            languages: languages,
+        }
+    }
+
+    /// Also search the locally edited pages in `overlay_dir`, which has a
+    /// subdirectory for each language.
+    pub fn with_overlay_dir(mut self, overlay_dir: PathBuf) -> PageDirs {
+        self.overlay_dir = Some(overlay_dir);
+        self
+    }
+
+    /// Return the directories of the languages that have pages, in order of
+    /// preference.
+    ///
+    /// Translated pages are expected in a sibling directory of the page
+    /// directory with the language as extension, like `pages.de` next to
+    /// `pages`. Languages without such a directory are skipped, unless
+    /// none of the languages has one.
+    pub fn language_dirs(&self) -> Result<Vec<LanguageDirs>, String> {
+        let mut dirs = vec![];
+        let mut missing = vec![];
+        for language in &self.languages {
+            let pages = if language == "en" {
+                self.page_dir.clone()
+            } else {
+                let mut dir_name = self.page_dir.file_name().unwrap_or_default().to_os_string();
+                dir_name.push(format!(".{}", language));
+                self.page_dir.with_file_name(dir_name)
+            };
+            if !pages.is_dir() {
+                debug!("No pages for language '{}' in {}", language, pages.display());
+                missing.push((language, pages));
+                continue;
+            }
+            dirs.push(LanguageDirs {
+                language: language.clone(),
+                pages: pages,
+                overlay: self.overlay_dir.as_ref().map(|dir| dir.join(language)),
+            });
+        }
+        if dirs.is_empty() {
+            return Err(match missing.first() {
+                Some(&(language, ref path)) if missing.len() == 1 => format!(
+                    "No pages for language '{}' found (expected directory {}).", language, path.display()
+                ),
+                _ => format!("No pages for any of the languages {} found.", self.languages.join(", ")),
+            });
+        }
+        Ok(dirs)
+    }
+
+    /// Return the directories to search for pages, in order of precedence.
+    ///
+    /// For each language, the platform specific directories come first. If
+    /// platform is not supported or if platform specific page does not
+    /// exist, the page is looked up in the "common" directory. Within each
+    /// of them, locally edited pages take precedence.
+    pub fn search_dirs(&self) -> Result<Vec<PathBuf>, String> {
+        let language_dirs = try!(self.language_dirs());
+        let dirs = platform_dirs(self.os).iter().chain(["common"].iter()).collect::<Vec<_>>();
+        let mut search_dirs = vec![];
+        for language_dirs in &language_dirs {
+            for dir in &dirs {
+                search_dirs.extend(language_dirs.roots().iter().map(|root| root.join(dir)));
+            }
+        }
+        Ok(search_dirs)
+    }
+
+    /// Search for a page and return the path to it.
+    ///
+    /// If there is no page for `name` itself, leading directories and a
+    /// trailing file extension are stripped (so that e.g. `/usr/bin/tar`
+    /// and `tar.exe` resolve to `tar`). With `ignore_case`, the lowercased
+    /// name is tried as well.
+    pub fn find_page(&self, name: &str, ignore_case: bool) -> Option<PathBuf> {
+        name_candidates(name, ignore_case).iter()
+                                          .filter_map(|candidate| self.find_page_exact(candidate))
+                                          .next()
+    }
+
+    /// Search for a page with exactly the given name and return the path to it.
+    pub fn find_page_exact(&self, name: &str) -> Option<PathBuf> {
+        if !is_page_name(name) {
+            return None;
+        }
+
+        // Build page file name
+        let page_filename = format!("{}.md", name);
+
+        // Get page directories
+        let dirs = match self.search_dirs() {
+            Ok(dirs) => dirs,
+            _ => return None,
+        };
+
+        dirs.iter()
+            .map(|dir| dir.join(&page_filename))
+            .find(|path| path.exists() && path.is_file())
+    }
+
+    /// Return the path to the page `name` in the "common" directory, if it exists.
+    pub fn find_common_page(&self, name: &str) -> Option<PathBuf> {
+        if !is_page_name(name) {
+            return None;
+        }
+        let language_dirs = match self.language_dirs() {
+            Ok(language_dirs) => language_dirs,
+            _ => return None,
+        };
+        language_dirs.iter()
+                     .flat_map(|language_dirs| language_dirs.roots())
+                     .map(|root| root.join("common").join(format!("{}.md", name)))
+                     .find(|path| path.is_file())
+    }
+}
+
+/// Return the platform directories of `os`, in order of precedence.
+///
+/// Upstream renamed `osx` to `macos`, so both names are searched for
+/// macOS pages in order to support old and new archives.
+pub fn platform_dirs(os: OsType) -> &'static [&'static str] {
+    match os {
+        OsType::Linux => &["linux"],
+        OsType::OsX => &["macos", "osx"],
+        OsType::SunOs => &[], // TODO: Does Rust support SunOS?
+        OsType::Other => &[],
+    }
+}
+
+/// Return whether `name` can be the name of a page file. Names must not
+/// be `.` or contain path separators, `..` or (for Windows drive prefixes)
+/// colons, so that joining them to a page directory stays within it.
+pub fn is_page_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && !name.contains(|c| c == '/' || c == '\\' || c == ':') && !name.contains("..")
+}
+
+/// Return the page names to try for a command name, in order of preference.
+/// Only valid page names (see `is_page_name`) are returned, so a path like
+/// `/usr/bin/tar` is only looked up by its basename.
+fn name_candidates(name: &str, ignore_case: bool) -> Vec<String> {
+    let mut candidates = vec![name.to_string()];
+
+    // Strip leading directories and the file extension
+    let basename = name.rsplit(|c| c == '/' || c == '\\').next().unwrap_or(name);
+    candidates.push(basename.to_string());
+    if let Some(pos) = basename.rfind('.') {
+        if pos > 0 {
+            candidates.push(basename[..pos].to_string());
+        }
+    }
+
+    if ignore_case {
+        let lowercase = candidates.iter().map(|c| c.to_lowercase()).collect::<Vec<_>>();
+        candidates.extend(lowercase);
+    }
+
+    let mut unique = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        if is_page_name(&candidate) && !unique.contains(&candidate) {
+            unique.push(candidate);
+        }
+    }
+    unique
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::{is_page_name, name_candidates, LanguageDirs};
+
+    #[test]
+    fn test_relative_overlay_path() {
+        let dirs = LanguageDirs {
+            language: "en".to_string(),
+            pages: PathBuf::from("/pages"),
+            overlay: Some(PathBuf::from("/data/overlay/en")),
+        };
+        assert_eq!(dirs.relative_overlay_path(Path::new("/data/overlay/en/common/tar.md")),
+                   Some(Path::new("common/tar.md")));
+        assert_eq!(dirs.relative_overlay_path(Path::new("/data/overlay/en/tar.md")), None);
+        assert_eq!(dirs.relative_overlay_path(Path::new("/data/overlay/en/de/common/tar.md")), None);
+        assert_eq!(dirs.relative_overlay_path(Path::new("/data/overlay/de/common/tar.md")), None);
+        assert_eq!(dirs.relative_overlay_path(Path::new("/pages/common/tar.md")), None);
+    }
+
+    #[test]
+    fn test_name_candidates() {
+        assert_eq!(name_candidates("tar", false), vec!["tar"]);
+        assert_eq!(name_candidates("/usr/bin/tar", false), vec!["tar"]);
+        assert_eq!(name_candidates("tar.exe", false), vec!["tar.exe", "tar"]);
+        assert_eq!(name_candidates("C:\\bin\\tar.exe", false), vec!["tar.exe", "tar"]);
+        assert_eq!(name_candidates(".bashrc", false), vec![".bashrc"]);
+    }
+
+    #[test]
+    fn test_name_candidates_paths() {
+        assert_eq!(name_candidates("/tmp/evil", false), vec!["evil"]);
+        assert_eq!(name_candidates("../../etc/passwd", false), vec!["passwd"]);
+        assert!(name_candidates("..", false).is_empty());
+        assert!(name_candidates("/", false).is_empty());
+        assert!(!is_page_name("../x"));
+        assert!(!is_page_name("C:x"));
+        assert!(is_page_name("git-commit"));
+    }
+
+    #[test]
+    fn test_name_candidates_ignore_case() {
+        assert_eq!(name_candidates("TAR", false), vec!["TAR"]);
+        assert_eq!(name_candidates("TAR", true), vec!["TAR", "tar"]);
+        assert_eq!(name_candidates("Tar.EXE", true), vec!["Tar.EXE", "Tar", "tar.exe", "tar"]);
+    }
+}
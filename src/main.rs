@@ -26,37 +26,60 @@
 #[cfg(feature = "logging")] extern crate env_logger;
 extern crate docopt;
 extern crate ansi_term;
-extern crate curl;
+#[cfg(feature = "network")] extern crate curl;
 extern crate rustc_serialize;
+extern crate tealdeer;
+extern crate term_size;
 extern crate walkdir;
+#[cfg(test)] extern crate tempdir;
 
-use std::io::BufReader;
-use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::fs::{self, File};
 use std::path::{Path, PathBuf};
 use std::process;
+use std::thread;
+use std::time::Duration;
+#[cfg(feature = "logging")] use std::time::{SystemTime, UNIX_EPOCH};
 
 use docopt::Docopt;
+use rustc_serialize::json;
+use walkdir::WalkDir;
 
-mod types;
-mod tokenizer;
-mod formatter;
+mod merge;
+mod missing;
 mod cache;
+mod diff;
+mod collate;
+mod config;
+mod daemon;
+mod editor;
 mod error;
-
+mod fuzzy;
+mod health;
+mod help;
+mod history;
+mod pager;
+mod project;
+mod search;
+mod stats;
+mod site;
+
+use tealdeer::{types, tokenizer, formatter, html, lookup, style};
 use tokenizer::Tokenizer;
 use cache::Cache;
 use error::TealdeerError::{UpdateError, CacheError};
-use formatter::print_lines;
+use formatter::{Format, format_columns, print_compact, print_lines, print_lines_accessible};
+use style::{ColourSupport, Styles};
 use types::OsType;
 use std::env;
-use std::process::Command;
 
 const NAME: &'static str = "tealdeer";
 const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 const USAGE: &'static str = "
 Usage:
 
-    tldr [options] <command>
+    tldr [options] export-site <out-dir>
+    tldr [options] <command>...
     tldr [options]
 
 Options:
@@ -64,65 +87,268 @@ Options:
     -h --help           Show this screen
     -v --version        Show version information
     -l --list           List all commands in the cache
-    -e --edit           Edit command in the cache
+    --output <format>   Output format of the list [text, json]
+    -a --apropos <kw>   Search page names and summaries for a keyword
+    -t --tag <tag>      Only list or search pages with the given tag
+    --top               List the most viewed pages
+    --complete <pre>    List commands starting with a prefix (for completion)
+    --missing           List commands in $PATH that have no page
+    -e --edit           Edit a local copy of a command
+    -d --diff           Show local modifications of an edited command
+    --reset             Discard local modifications of a command
+    --all               With --reset, discard all local modifications
+    --info              Show information about a command's page
+    -i --ignore-case    Look up pages case-insensitively
+    -m --merge          Add examples from the common page to platform pages
     -f --render <file>  Render a specific markdown file
-    -o --os <type>      Override the operating system [linux, osx, sunos]
+    --render-dir <dir>  Render all markdown files in a directory tree
+    -w --watch          With --render, render the file again whenever it changes
+    -o --os <type>      Override the operating system [linux, macos, sunos]
+    -L --language <l>   Use the pages translated to a language (e.g. de), or the
+                        first of several languages that has a page (e.g. pt_BR:pt:en)
+    --accessible        Screen reader friendly output
+    --no-pager          Never page output that does not fit on the terminal
+    --init-project      Create a .tldr/pages skeleton in the current directory
+    --submit            Create a new page for a command from the template
+    --daemon            Serve page lookups over a local socket
+    --warm-from-history <file>  Load the pages of the most used commands of a
+                        shell history file into the daemon
+    --health            Check the configuration and the cache
+    --log-format <fmt>  Format of the log output [text, json]
+    --output-file <f>   Write rendered pages to a file (.html, .md or text)
+    --command-not-found <cmd>  Print a short summary of a command (for shell hooks)
+    --help-fallback     Show the --help output of commands without a page
+    --verbose           Report the language and path of each shown page
+    -y --yes            Show the closest match of a misspelled command without asking
 
 Examples:
 
     $ tldr tar
+    $ tldr tar gzip xz
     $ tldr --list
+    $ tldr --list --output json
+    $ tldr --list --language de
+    $ tldr --apropos archive
+    $ tldr --list --tag deploy
 
 To render a local file (for testing):
 
     $ tldr --render /path/to/file.md
+    $ tldr --render /path/to/file.md --watch
+    $ tldr --render-dir /path/to/pages
+
+To start writing project-local pages:
+
+    $ tldr --init-project
+
+To publish the pages as a static HTML site:
+
+    $ tldr export-site /path/to/site
+
+To contribute a missing page:
+
+    $ tldr --submit <command>
 ";
+const WATCH_INTERVAL_MS: u64 = 250;
 const ARCHIVE_URL: &'static str = "https://github.com/tldr-pages/tldr/archive/master.tar.gz";
 
 #[derive(Debug, RustcDecodable)]
 struct Args {
-    arg_command: Option<String>,
+    cmd_export_site: bool,
+    arg_out_dir: String,
+    arg_command: Vec<String>,
     flag_help: bool,
     flag_version: bool,
     flag_list: bool,
+    flag_output: Option<String>,
+    flag_apropos: Option<String>,
+    flag_tag: Option<String>,
+    flag_top: bool,
+    flag_complete: Option<String>,
+    flag_missing: bool,
     flag_edit: bool,
+    flag_diff: bool,
+    flag_reset: bool,
+    flag_all: bool,
+    flag_info: bool,
+    flag_ignore_case: bool,
+    flag_merge: bool,
     flag_render: Option<String>,
+    flag_render_dir: Option<String>,
+    flag_watch: bool,
     flag_os: Option<OsType>,
+    flag_language: Option<String>,
+    flag_accessible: bool,
+    flag_no_pager: bool,
+    flag_init_project: bool,
+    flag_submit: bool,
+    flag_daemon: bool,
+    flag_warm_from_history: Option<String>,
+    flag_health: bool,
+    flag_log_format: Option<String>,
+    flag_output_file: Option<String>,
+    flag_command_not_found: Option<String>,
+    flag_help_fallback: bool,
+    flag_yes: bool,
+    flag_verbose: bool,
+}
+
+/// Return the name of the page at `path`.
+fn page_name(path: &Path) -> String {
+    path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default()
 }
 
-/// Print page by path
-fn print_page(path: &Path) -> Result<(), String> {
+/// Print page by path. `language` is the language of the page.
+fn print_page<W: Write>(path: &Path, language: &str, styles: &Styles, format: Format,
+                        out: &mut W) -> Result<(), String> {
     // Open file
     let file = try!(
         File::open(path).map_err(|msg| format!("Could not open file: {}", msg))
     );
-    let reader = BufReader::new(file);
-
-    // Create tokenizer and print output
-    let mut tokenizer = Tokenizer::new(reader);
-    print_lines(&mut tokenizer);
+    print_markdown(BufReader::new(file), &page_name(path), language, styles, format, out)
+}
 
-    Ok(())
+/// Print a platform specific page followed by the examples of the common
+/// page that it does not contain
+fn print_merged_page<W: Write>(path: &Path, common_path: &Path, language: &str, styles: &Styles,
+                               format: Format, out: &mut W) -> Result<(), String> {
+    let read = |path: &Path| {
+        let mut content = String::new();
+        File::open(path).and_then(|mut file| file.read_to_string(&mut content))
+                        .map(|_| content)
+                        .map_err(|msg| format!("Could not open file: {}", msg))
+    };
+    let merged = merge::merge_pages(&try!(read(path)), &try!(read(common_path)));
+    print_markdown(merged.as_bytes(), &page_name(path), language, styles, format, out)
 }
 
-/// Edit page by path
-fn edit_page(path: &Path) -> Result<(), String> {
-    if let Ok(editor) = env::var("EDITOR") {
-        let _ = Command::new(editor)
-            .arg(format!("{}",path.display()))
-            .spawn();
-        return Ok(());
+/// Print markdown from a reader. The `name` and `language` of the page are
+/// only used for HTML documents.
+fn print_markdown<R: BufRead, W: Write>(mut reader: R, name: &str, language: &str, styles: &Styles, format: Format,
+                                        out: &mut W) -> Result<(), String> {
+    let result = match format {
+        Format::Markdown => io::copy(&mut reader, out).map(|_| ()),
+        Format::Html => out.write_all(html::render_page(&mut Tokenizer::new(reader), name, language, None).as_bytes()),
+        Format::Ansi => print_lines(&mut Tokenizer::new(reader), styles, out),
+        Format::Accessible => print_lines_accessible(&mut Tokenizer::new(reader), out),
     };
-    return Err("$EDITOR is not set.".to_string());
+    result.map_err(|e| format!("Could not write output: {}", e))
+}
+
+/// Return the format to write pages to `--output-file` in, given by its
+/// extension: `.html` for HTML documents and `.md` for the pages as they
+/// are. Other files get `default`, without colours.
+fn output_file_format(path: &str, default: Format) -> Format {
+    let extension = Path::new(path).extension().map(|ext| ext.to_string_lossy().to_lowercase());
+    match extension.as_ref().map(|ext| &ext[..]) {
+        Some("html") | Some("htm") => Format::Html,
+        Some("md") => Format::Markdown,
+        _ => default,
+    }
+}
+
+/// Write what precedes the page `name` if several pages are printed: a
+/// `==> name <==` header, or an empty line between markdown pages, which
+/// start with their own title.
+fn write_page_separator<W: Write>(out: &mut W, format: Format, name: &str, first: bool) -> io::Result<()> {
+    match format {
+        Format::Ansi | Format::Accessible => writeln!(out, "==> {} <==", name),
+        Format::Markdown if !first => writeln!(out),
+        _ => Ok(()),
+    }
+}
+
+/// Open the output for rendered pages: the file given with `--output-file`,
+/// or stdout.
+fn open_output(output_file: &Option<String>) -> Box<dyn Write> {
+    match *output_file {
+        Some(ref path) => match File::create(path) {
+            Ok(file) => Box::new(file),
+            Err(e) => {
+                println!("Could not create {}: {}", path, e);
+                process::exit(1);
+            },
+        },
+        None => Box::new(io::stdout()),
+    }
+}
+
+/// Render the page at `path` again whenever its modification time changes,
+/// until the process is interrupted. The screen is cleared before each
+/// rendering if the page is shown on a terminal.
+fn watch_page(path: &Path, language: &str, styles: &Styles, format: Format, output_file: &Option<String>) -> ! {
+    let clear = output_file.is_none() && ColourSupport::detect() != ColourSupport::NoColour
+                && term_size::dimensions_stdout().is_some();
+    let mut last_modified = None;
+    loop {
+        let modified = fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+        if modified != last_modified {
+            last_modified = modified;
+            if clear {
+                print!("\x1b[2J\x1b[H");
+            }
+            // The file may briefly be missing while an editor saves it
+            if let Err(msg) = print_page(path, language, styles, format, &mut open_output(output_file)) {
+                eprintln!("{}", msg);
+            }
+            let _ = io::stdout().flush();
+        }
+        thread::sleep(Duration::from_millis(WATCH_INTERVAL_MS));
+    }
+}
+
+/// Find the page that was probably meant by a command without a page, and
+/// ask whether to show it (unless `assume_yes` is set). Nothing is asked
+/// if stdin is not a terminal.
+fn confirm_best_match(cache: &Cache, command: &str, assume_yes: bool) -> Option<PathBuf> {
+    let pages = cache.list_pages().unwrap_or_else(|_| vec![]);
+    let name = fuzzy::best_match(command, &pages)?;
+    if !assume_yes {
+        let _ = term_size::dimensions_stdin()?;
+        // Ask on stderr, so that the question does not end up in the output
+        eprint!("Show page for '{}'? [Y/n] ", name);
+        let _ = io::stderr().flush();
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer).is_err() {
+            return None;
+        }
+        match &answer.trim().to_lowercase()[..] {
+            "" | "y" | "yes" => {},
+            _ => return None,
+        }
+    }
+    cache.find_page(name, false)
+}
+
+/// Format a log record as a single line JSON object.
+#[cfg(feature = "logging")]
+fn format_log_json(record: &log::LogRecord) -> String {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    format!("{{\"time\":{},\"level\":\"{}\",\"target\":{},\"message\":{}}}",
+            time, record.level(),
+            json::encode(&record.target()).unwrap_or_else(|_| "\"\"".into()),
+            json::encode(&record.args().to_string()).unwrap_or_else(|_| "\"\"".into()))
 }
 
 #[cfg(feature = "logging")]
-fn init_log() {
-    env_logger::init().unwrap();
+fn init_log(json: bool) {
+    let mut builder = env_logger::LogBuilder::new();
+    if json {
+        let _ = builder.format(format_log_json);
+    }
+    if let Ok(filters) = env::var("RUST_LOG") {
+        let _ = builder.parse(&filters);
+    }
+    builder.init().unwrap();
 }
 
 #[cfg(not(feature = "logging"))]
-fn init_log() { }
+fn init_log(json: bool) {
+    if json {
+        println!("JSON log output requires a build with the 'logging' feature.");
+        process::exit(1);
+    }
+}
 
 #[cfg(target_os = "linux")]
 fn get_os() -> OsType { OsType::Linux }
@@ -134,33 +360,165 @@ fn get_os() -> OsType { OsType::OsX }
 fn get_os() -> OsType { OsType::Other }
 
 fn main() {
-    // Initialize logger
-    init_log();
-
     // Parse arguments
     let args: Args = Docopt::new(USAGE)
                             .and_then(|d| d.decode())
                             .unwrap_or_else(|e| e.exit());
 
+    // Initialize logger
+    match args.flag_log_format.as_ref().map(|s| &s[..]) {
+        None | Some("text") => init_log(false),
+        Some("json") => init_log(true),
+        Some(other) => {
+            println!("Unknown log format: {}. Choose one of 'text' or 'json'.", other);
+            process::exit(1);
+        },
+    }
+
     // Show version and exit
     if args.flag_version {
         println!("{} v{}", NAME, VERSION);
         process::exit(0);
     }
 
-    // Specify target OS
+    // Create project page skeleton and exit
+    if args.flag_init_project {
+        let cwd = env::current_dir().unwrap_or_else(|e| {
+            println!("Could not determine current directory: {}", e);
+            process::exit(1);
+        });
+        match project::init_project(&cwd) {
+            Ok(pages_dir) => {
+                println!("Created project pages in {}", pages_dir.display());
+                println!("Point $TLDR_PAGE_DIR to it to use them.");
+                process::exit(0);
+            },
+            Err(msg) => {
+                println!("{}", msg);
+                process::exit(1);
+            },
+        }
+    }
+
+    // Specify target OS. The default can be overridden through
+    // $TLDR_DEFAULT_PLATFORM, which in turn is overridden by --os.
+    let default_os = config::var("TLDR_DEFAULT_PLATFORM").and_then(|value| match value {
+        Some(value) => value.parse().map_err(|msg| format!("Invalid $TLDR_DEFAULT_PLATFORM: {}", msg)),
+        None => Ok(get_os()),
+    });
+
+    // Languages in order of preference, overridden by --language
+    let languages = config::languages(args.flag_language.as_ref().map(|lang| &lang[..]));
+
+    // Run diagnostics and exit
+    if args.flag_health {
+        let os = args.flag_os.or_else(|| default_os.clone().ok()).unwrap_or_else(get_os);
+        let languages = languages.clone().unwrap_or_else(|_| vec![]);
+        let checks = health::run(&Cache::new(ARCHIVE_URL, os, languages));
+        for check in &checks {
+            match check.result {
+                Ok(ref msg) => println!("PASS {}: {}", check.name, msg),
+                Err(ref msg) => println!("FAIL {}: {}", check.name, msg),
+            }
+        }
+        process::exit(if checks.iter().all(|check| check.result.is_ok()) { 0 } else { 1 });
+    }
+
     let os: OsType = match args.flag_os {
         Some(os) => os,
-        None => get_os(),
+        None => default_os.unwrap_or_else(|msg| {
+            println!("{}", msg);
+            process::exit(1);
+        }),
     };
 
+    let languages = languages.unwrap_or_else(|msg| {
+        println!("{}", msg);
+        process::exit(1);
+    });
+
     // Initialize cache
-    let cache = Cache::new(ARCHIVE_URL, os);
+    let cache = Cache::new(ARCHIVE_URL, os, languages.clone());
+
+    // Serve page lookups until killed
+    if args.flag_daemon {
+        if let Err(msg) = daemon::serve(ARCHIVE_URL) {
+            println!("{}", msg);
+            process::exit(1);
+        }
+        process::exit(0);
+    }
+
+    // Load the pages of frequently used commands into the daemon and exit
+    if let Some(ref file) = args.flag_warm_from_history {
+        const WARM_PAGES: usize = 100;
+        if !daemon::is_running() {
+            println!("No daemon is running. Start one with `tldr --daemon`.");
+            process::exit(1);
+        }
+        let mut content = vec![];
+        if let Err(e) = File::open(file).and_then(|mut file| file.read_to_end(&mut content)) {
+            println!("Could not read {}: {}", file, e);
+            process::exit(1);
+        }
+        let content = String::from_utf8_lossy(&content);
+        let warmed = history::most_used(&content, WARM_PAGES).iter()
+                                                             .filter(|command| daemon::lookup(os, &languages, false, command).is_some())
+                                                             .count();
+        println!("Loaded {} pages into the daemon.", warmed);
+        process::exit(0);
+    }
+
+    // Export pages as HTML and exit
+    if args.cmd_export_site {
+        match site::export_site(&cache, Path::new(&args.arg_out_dir)) {
+            Ok(count) => {
+                println!("Exported {} pages to {}", count, args.arg_out_dir);
+                process::exit(0);
+            },
+            Err(msg) => {
+                println!("Could not export pages: {}", msg);
+                process::exit(1);
+            },
+        }
+    }
+
+    // Load styles
+    let mut styles = Styles::from_env().unwrap_or_else(|msg| {
+        println!("{}", msg);
+        process::exit(1);
+    });
+    styles.highlight = config::flag("TLDR_HIGHLIGHT");
+
+    // Determine output format
+    let format = if args.flag_accessible || config::flag("TLDR_ACCESSIBLE") {
+        Format::Accessible
+    } else {
+        Format::Ansi
+    };
+
+    // Output written to a file is not coloured, and may be in a format
+    // given by the file extension
+    let styles = if args.flag_output_file.is_some() { styles.without_colours() } else { styles };
+    let format = match args.flag_output_file {
+        Some(ref path) => output_file_format(path, format),
+        None => format,
+    };
+    let multiple_pages = args.flag_render_dir.is_some() || args.arg_command.len() > 1;
+    if format == Format::Html && multiple_pages {
+        println!("Only a single page can be written to an HTML file. \
+                  Use `tldr export-site` to export several pages.");
+        process::exit(1);
+    }
 
     // Render local file and exit
     if let Some(ref file) = args.flag_render {
         let path = PathBuf::from(file);
-        if let Err(msg) = print_page(&path) {
+        let language = cache.page_language(&path);
+        if args.flag_watch {
+            watch_page(&path, &language, &styles, format, &args.flag_output_file);
+        }
+        if let Err(msg) = print_page(&path, &language, &styles, format, &mut open_output(&args.flag_output_file)) {
             println!("{}", msg);
             process::exit(1);
         } else {
@@ -168,52 +526,431 @@ fn main() {
         };
     }
 
+    // Summarize a command for a shell's command-not-found hook and exit.
+    // Nothing is printed if there is no page, so that the shell can fall
+    // back to its own message.
+    if let Some(ref command) = args.flag_command_not_found {
+        let path = cache.find_page(command, args.flag_ignore_case).unwrap_or_else(|| process::exit(1));
+        let file = File::open(path).unwrap_or_else(|_| process::exit(1));
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        match print_compact(&mut Tokenizer::new(BufReader::new(file)), &styles, 2, &mut out) {
+            Ok(()) => process::exit(0),
+            Err(_) => process::exit(1),
+        }
+    }
+
+    // Render all local files in a directory and exit
+    if let Some(ref dir) = args.flag_render_dir {
+        let root = Path::new(dir);
+        let mut paths = WalkDir::new(root)
+                                .follow_links(true)
+                                .into_iter()
+                                .filter_map(|e| e.ok())
+                                .filter(|e| {
+                                    let extension = e.path().extension().and_then(|s| s.to_str()).unwrap_or("");
+                                    e.file_type().is_file() && extension == "md"
+                                })
+                                .map(|e| e.path().to_path_buf())
+                                .collect::<Vec<_>>();
+        if paths.is_empty() {
+            println!("No markdown files found in {}", dir);
+            process::exit(1);
+        }
+        paths.sort_by(|a, b| collate::natural_cmp(&a.to_string_lossy(), &b.to_string_lossy()));
+
+        let mut out = open_output(&args.flag_output_file);
+        let mut failed = false;
+        for path in &paths {
+            let name = path.strip_prefix(root).unwrap_or(path);
+            if let Err(e) = write_page_separator(&mut out, format, &name.display().to_string(), path == &paths[0]) {
+                println!("Could not write output: {}", e);
+                process::exit(1);
+            }
+            if let Err(msg) = print_page(path, &cache.page_language(path), &styles, format, &mut out) {
+                println!("{}: {}", path.display(), msg);
+                failed = true;
+            }
+        }
+        process::exit(if failed { 1 } else { 0 });
+    }
+
     // List cached commands and exit
     if args.flag_list {
-        // Get list of pages
-        let pages = cache.list_pages().unwrap_or_else(|e| {
+        match args.flag_output.as_ref().map(|s| &s[..]) {
+            None | Some("text") => {
+                // Get list of pages. Filtering by tag requires reading the
+                // metadata of every page.
+                let pages = match args.flag_tag {
+                    Some(ref tag) => cache.list_page_infos().map(|infos| {
+                        infos.into_iter()
+                             .filter(|info| info.tags.contains(tag))
+                             .map(|info| info.name)
+                             .collect()
+                    }),
+                    None => cache.list_pages(),
+                };
+                let pages = pages.unwrap_or_else(|e| {
+                    match e {
+                        UpdateError(msg) | CacheError(msg) => println!("Could not get list of pages: {}", msg),
+                    }
+                    process::exit(1);
+                });
+
+                // Print pages in columns on a terminal, one per line otherwise
+                match term_size::dimensions_stdout() {
+                    Some((width, _)) => print!("{}", format_columns(&pages, width)),
+                    None => for page in &pages {
+                        println!("{}", page);
+                    },
+                }
+            },
+            Some("json") => {
+                let mut pages = cache.list_page_infos().unwrap_or_else(|e| {
+                    match e {
+                        UpdateError(msg) | CacheError(msg) => println!("Could not get list of pages: {}", msg),
+                    }
+                    process::exit(1);
+                });
+                if let Some(ref tag) = args.flag_tag {
+                    pages.retain(|info| info.tags.contains(tag));
+                }
+                match json::encode(&pages) {
+                    Ok(encoded) => println!("{}", encoded),
+                    Err(e) => {
+                        println!("Could not encode list of pages: {}", e);
+                        process::exit(1);
+                    },
+                }
+            },
+            Some(other) => {
+                println!("Invalid output format: '{}'. Choose one of 'text' or 'json'.", other);
+                process::exit(1);
+            },
+        }
+        process::exit(0);
+    }
+
+    // Print commands for shell completion and exit. Errors are not printed,
+    // since the output is consumed by completion functions.
+    if let Some(ref prefix) = args.flag_complete {
+        let pages = cache.list_pages().unwrap_or_else(|_| process::exit(1));
+        for page in pages.iter().filter(|page| page.starts_with(&prefix[..])) {
+            println!("{}", page);
+        }
+        process::exit(0);
+    }
+
+    // List commands without pages and exit
+    if args.flag_missing {
+        let commands = missing::missing_pages(&cache).unwrap_or_else(|e| {
             match e {
                 UpdateError(msg) | CacheError(msg) => println!("Could not get list of pages: {}", msg),
             }
             process::exit(1);
         });
+        for command in &commands {
+            println!("{}", command);
+        }
+        process::exit(0);
+    }
+
+    // List most viewed pages and exit
+    if args.flag_top {
+        if !stats::enabled() {
+            println!("View tracking is disabled. Set $TLDR_TRACK_VIEWS=1 to enable it.");
+            process::exit(1);
+        }
+        let pages = stats::top(10).unwrap_or_else(|msg| {
+            println!("Could not read view counts: {}", msg);
+            process::exit(1);
+        });
+        for (name, count) in pages {
+            println!("{:>6} {}", count, name);
+        }
+        process::exit(0);
+    }
 
-        // Print pages
-        println!("{}", pages.join(", "));
+    // Search page names and summaries and exit
+    if let Some(ref keyword) = args.flag_apropos {
+        let tag = args.flag_tag.as_ref().map(|tag| &tag[..]);
+        let matches = search::apropos(&cache, keyword, tag).unwrap_or_else(|e| {
+            match e {
+                UpdateError(msg) | CacheError(msg) => println!("Could not search pages: {}", msg),
+            }
+            process::exit(1);
+        });
+        if matches.is_empty() {
+            println!("{}: nothing appropriate.", keyword);
+            process::exit(1);
+        }
+        for (name, summary) in matches {
+            println!("{} \u{2014} {}", name, summary);
+        }
         process::exit(0);
     }
 
-    // Edit the cached command markdown and exit
+    // Edit the command markdown in the overlay directory and exit
     if args.flag_edit {
-        if let Some(ref command) = args.arg_command {
-            if let Some(path) = cache.find_page_to_edit(&command) {
-                if let Err(msg) = edit_page(&path) {
-                    println!("{}", msg);
-                } else {
-                    process::exit(0);
+        if let Some(command) = args.arg_command.first() {
+            let path = cache.prepare_page_to_edit(command).unwrap_or_else(|e| {
+                match e {
+                    UpdateError(msg) | CacheError(msg) => println!("{}", msg),
                 }
+                process::exit(1);
+            });
+            if let Err(msg) = editor::edit(&path) {
+                println!("{}", msg);
+                process::exit(1);
             }
+            process::exit(0);
         }
         println!("You must specify command to edit tldr-markdown.");
         process::exit(1);
     }
 
-    // Show command from cache
-    if let Some(ref command) = args.arg_command {
-        // Search for command in cache
-        if let Some(path) = cache.find_page(&command) {
-            if let Err(msg) = print_page(&path) {
+    // Show local modifications of a page and exit
+    if args.flag_diff {
+        let command = match args.arg_command.first() {
+            Some(command) => command,
+            None => {
+                println!("You must specify the command to compare.");
+                process::exit(1);
+            },
+        };
+        let path = match cache.find_page(command, args.flag_ignore_case) {
+            Some(path) => path,
+            None => {
+                println!("Page {} not found in cache", command);
+                process::exit(1);
+            },
+        };
+        let original = match cache.find_upstream_page(&path) {
+            Some(original) => original,
+            None => {
+                println!("Page {} has not been edited locally.", command);
+                process::exit(0);
+            },
+        };
+        let read = |path: &Path| {
+            let mut content = String::new();
+            File::open(path).and_then(|mut file| file.read_to_string(&mut content))
+                            .map(|_| content)
+                            .unwrap_or_else(|e| {
+                                println!("Could not read {}: {}", path.display(), e);
+                                process::exit(1);
+                            })
+        };
+        match diff::unified_diff(&read(&original), &read(&path),
+                                 &original.display().to_string(), &path.display().to_string(),
+                                 ColourSupport::detect() != ColourSupport::NoColour) {
+            Some(diff) => print!("{}", diff),
+            None => println!("Page {} has no local modifications.", command),
+        }
+        process::exit(0);
+    }
+
+    // Discard local modifications and exit
+    if args.flag_reset {
+        let result = if args.flag_all {
+            cache.reset_all_pages()
+        } else if !args.arg_command.is_empty() {
+            cache.reset_pages(&args.arg_command)
+        } else {
+            println!("You must specify the command to reset, or --all.");
+            process::exit(1);
+        };
+        match result {
+            Ok(0) => println!("No local modifications found."),
+            Ok(count) => println!("Discarded local modifications of {} page(s).", count),
+            Err(UpdateError(msg)) | Err(CacheError(msg)) => {
+                println!("Could not discard local modifications: {}", msg);
+                process::exit(1);
+            },
+        }
+        process::exit(0);
+    }
+
+    // Create a new page from the template and exit
+    if args.flag_submit {
+        let command = match args.arg_command.first() {
+            Some(command) => command,
+            None => {
+                println!("You must specify the command to write a page for.");
+                process::exit(1);
+            },
+        };
+        if cache.find_page(command, false).is_some() {
+            println!("Page {} exists already. Edit it with `tldr --edit {}`.", command, command);
+            process::exit(1);
+        }
+        let cwd = env::current_dir().unwrap_or_else(|e| {
+            println!("Could not determine current directory: {}", e);
+            process::exit(1);
+        });
+        match project::create_page(&cwd, command) {
+            Ok(path) => {
+                println!("Created {} from the page template.", path.display());
+                println!("Once it is filled in, submit it as a pull request to:");
+                println!("{}", project::new_page_url(command, &project::page_template(command)));
+                process::exit(0);
+            },
+            Err(msg) => {
                 println!("{}", msg);
                 process::exit(1);
+            },
+        }
+    }
+
+    // Show information about a page and exit
+    if args.flag_info {
+        let command = match args.arg_command.first() {
+            Some(command) => command,
+            None => {
+                println!("You must specify the command to show information about.");
+                process::exit(1);
+            },
+        };
+        let path = match cache.find_page(command, args.flag_ignore_case) {
+            Some(path) => path,
+            None => {
+                println!("Page {} not found in cache", command);
+                process::exit(1);
+            },
+        };
+        let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or(command).to_string();
+        let info = cache.page_info(name, path);
+        println!("Name:     {}", info.name);
+        println!("Platform: {}", info.platform);
+        println!("Language: {}", info.language);
+        println!("Path:     {}", info.path);
+        if let Some(ref author) = info.author {
+            println!("Author:   {}", author);
+        }
+        if !info.tags.is_empty() {
+            println!("Tags:     {}", info.tags.join(", "));
+        }
+        if !info.related.is_empty() {
+            println!("Related:  {}", info.related.join(", "));
+        }
+        process::exit(0);
+    }
+
+    // Show commands from cache
+    if !args.arg_command.is_empty() {
+        let show_headers = args.arg_command.len() > 1;
+        let mut found = 0;
+        let mut missing = vec![];
+
+        // Pages shown on a terminal are collected first, to page them if
+        // they don't fit. Output to a pipe or a file is never paged.
+        let paged = args.flag_output_file.is_none() && !args.flag_no_pager && !pager::is_disabled() &&
+                    pager::is_available();
+        let mut page_buffer = vec![];
+        let mut output;
+        let mut out: &mut dyn Write = if paged {
+            &mut page_buffer
+        } else {
+            output = open_output(&args.flag_output_file);
+            &mut output
+        };
+        let help_fallback = args.flag_help_fallback || config::flag("TLDR_HELP_FALLBACK");
+        for command in &args.arg_command {
+            // Ask the daemon first, if one is running
+            let hot_page = if args.flag_merge {
+                None
             } else {
-                process::exit(0);
+                daemon::lookup(os, &languages, args.flag_ignore_case, command)
+            };
+
+            // Search for command in cache
+            let path = match hot_page {
+                Some((ref path, _)) => path.clone(),
+                None => match cache.find_page(command, args.flag_ignore_case) {
+                    Some(path) => path,
+                    None => {
+                        // Show the command's own help instead, if enabled
+                        if let Some(help) = if help_fallback { help::run_help(command) } else { None } {
+                            if show_headers {
+                                if let Err(e) = write_page_separator(&mut out, format, command, found == 0) {
+                                    println!("Could not write output: {}", e);
+                                    process::exit(1);
+                                }
+                            }
+                            let page = help::help_to_page(command, &help);
+                            match print_markdown(page.as_bytes(), command, "en", &styles, format, &mut out) {
+                                Ok(()) => found += 1,
+                                Err(msg) => println!("{}", msg),
+                            }
+                            continue;
+                        }
+                        // Offer the closest page, in case of a typo
+                        match confirm_best_match(&cache, command, args.flag_yes) {
+                            Some(path) => path,
+                            None => {
+                                missing.push(command);
+                                continue;
+                            },
+                        }
+                    },
+                },
+            };
+            if show_headers {
+                if let Err(e) = write_page_separator(&mut out, format, command, found == 0) {
+                    println!("Could not write output: {}", e);
+                    process::exit(1);
+                }
+            }
+            let common_path = if args.flag_merge {
+                path.file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|name| cache.find_common_page(name))
+                    .and_then(|common_path| if common_path != path { Some(common_path) } else { None })
+            } else {
+                None
+            };
+            let language = cache.page_language(&path);
+            let result = match (common_path, hot_page) {
+                (Some(common_path), _) => print_merged_page(&path, &common_path, &language, &styles, format, &mut out),
+                (None, Some((_, content))) => {
+                    print_markdown(content.as_bytes(), &page_name(&path), &language, &styles, format, &mut out)
+                },
+                (None, None) => print_page(&path, &language, &styles, format, &mut out),
+            };
+            if let Err(msg) = result {
+                println!("{}", msg);
+                continue;
+            }
+            found += 1;
+            if args.flag_verbose {
+                eprintln!("Page {} is the '{}' page {}", command, language, path.display());
+            }
+            if cache.find_newer_upstream_page(&path).is_some() {
+                eprintln!("Note: page {} was updated since you edited it. \
+                           Run `tldr --diff {}` to compare.", command, command);
+            }
+            if stats::enabled() {
+                let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or(command);
+                if let Err(msg) = stats::record_view(name) {
+                    warn!("Could not record page view: {}", msg);
+                }
+            }
+        }
+        if paged {
+            if let Err(e) = pager::page(&page_buffer) {
+                println!("Could not write output: {}", e);
+                process::exit(1);
+            }
+        }
+
+        if !missing.is_empty() {
+            for command in &missing {
+                println!("Page {} not found in cache", command);
             }
-        } else {
-            println!("Page {} not found in cache", &command);
             println!("Try updating with `tldr --update`, or submit a pull request to:");
             println!("https://github.com/tldr-pages/tldr");
-            process::exit(1);
         }
+        process::exit(if found > 0 { 0 } else { 1 });
     }
 
     // Some flags can be run without a command.
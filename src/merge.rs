@@ -0,0 +1,58 @@
+//! Merging of platform specific and common pages.
+
+use tokenizer::Tokenizer;
+use types::LineType;
+
+/// Return the examples of a page as `(description, command)` pairs.
+fn examples(page: &str) -> Vec<(String, String)> {
+    let mut tokenizer = Tokenizer::new(page.as_bytes());
+    let mut examples = vec![];
+    let mut description = None;
+    while let Some(token) = tokenizer.next_token() {
+        match token {
+            LineType::ExampleText(text) => description = Some(text),
+            LineType::ExampleCode(code) => {
+                if let Some(text) = description.take() {
+                    examples.push((text, code));
+                }
+            },
+            _ => {},
+        }
+    }
+    examples
+}
+
+/// Append the examples of the `common` page that are not contained in the
+/// `platform` page to the `platform` page.
+///
+/// Examples are considered duplicates if their commands are equal.
+pub fn merge_pages(platform: &str, common: &str) -> String {
+    let existing = examples(platform);
+    let mut merged = platform.trim_end().to_string();
+    merged.push('\n');
+    for (text, code) in examples(common) {
+        if !existing.iter().any(|(_, existing_code)| *existing_code == code) {
+            merged.push_str(&format!("\n- {}\n\n`{}`\n", text, code));
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod test {
+    use super::merge_pages;
+
+    #[test]
+    fn test_merge_pages() {
+        let platform = "# ls\n\n> List files.\n\n- List all files:\n\n`ls -a`\n";
+        let common = "# ls\n\n> List.\n\n- Show hidden files:\n\n`ls -a`\n\n- Long format:\n\n`ls -l`\n";
+        assert_eq!(merge_pages(platform, common),
+                   "# ls\n\n> List files.\n\n- List all files:\n\n`ls -a`\n\n- Long format:\n\n`ls -l`\n");
+    }
+
+    #[test]
+    fn test_merge_pages_no_extra_examples() {
+        let page = "# ls\n\n> List files.\n\n- List all files:\n\n`ls -a`\n";
+        assert_eq!(merge_pages(page, page), page);
+    }
+}
@@ -0,0 +1,64 @@
+//! Finding commands in `$PATH` that have no page.
+
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use cache::Cache;
+use collate;
+use error::TealdeerError;
+
+/// Return whether the file at `path` is executable.
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    match fs::metadata(path) {
+        Ok(metadata) => metadata.is_file() && metadata.permissions().mode() & 0o111 != 0,
+        Err(_) => false,
+    }
+}
+
+/// Return whether the file at `path` is executable.
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("").to_lowercase();
+    path.is_file() && (extension == "exe" || extension == "bat" || extension == "cmd")
+}
+
+/// Return the names of all executables in the directories of `$PATH`.
+pub fn path_commands() -> BTreeSet<String> {
+    let mut commands = BTreeSet::new();
+    let path_var = match env::var_os("PATH") {
+        Some(path_var) => path_var,
+        None => return commands,
+    };
+    for dir in env::split_paths(&path_var) {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!("Could not read {}: {}", dir.display(), e);
+                continue;
+            },
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !is_executable(&path) {
+                continue;
+            }
+            let name = if cfg!(unix) { path.file_name() } else { path.file_stem() };
+            if let Some(name) = name.and_then(|name| name.to_str()) {
+                let _ = commands.insert(name.to_string());
+            }
+        }
+    }
+    commands
+}
+
+/// Return the commands in `$PATH` for which the cache has no page.
+pub fn missing_pages(cache: &Cache) -> Result<Vec<String>, TealdeerError> {
+    let pages = try!(cache.list_pages()).into_iter().collect::<BTreeSet<_>>();
+    let mut missing = path_commands().into_iter().filter(|command| !pages.contains(command)).collect::<Vec<_>>();
+    missing.sort_by(|a, b| collate::natural_cmp(a, b));
+    Ok(missing)
+}
@@ -0,0 +1,287 @@
+//! Paging output that does not fit on the terminal.
+//!
+//! Output is piped through `$PAGER` if it is set. Otherwise a minimal
+//! built-in pager shows one screen at a time. It reads single key presses
+//! by switching the terminal out of line mode with `stty`, so it is only
+//! available where `stty` is (not on Windows); elsewhere the output is
+//! written as is. Setting `$TLDR_PAGER` to `0` turns paging off.
+
+use std::env;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+
+use term_size;
+
+use editor::split_command;
+
+/// What a key press does in the built-in pager.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum Action {
+    NextPage,
+    PreviousPage,
+    NextLine,
+    PreviousLine,
+    Top,
+    Bottom,
+    Quit,
+    Ignore,
+}
+
+/// Read the next key press from `keys` and return what it does, or `None`
+/// at the end of the input.
+fn read_action<I: Iterator<Item = u8>>(keys: &mut I) -> Option<Action> {
+    let action = match keys.next() {
+        None => return None,
+        Some(b' ') | Some(b'f') => Action::NextPage,
+        Some(b'b') => Action::PreviousPage,
+        Some(b'\n') | Some(b'\r') | Some(b'j') => Action::NextLine,
+        Some(b'k') => Action::PreviousLine,
+        Some(b'g') => Action::Top,
+        Some(b'G') => Action::Bottom,
+        // Ctrl-C is read as a key, since signals are off while paging
+        Some(b'q') | Some(b'Q') | Some(3) => Action::Quit,
+        // Arrow keys and page up/down send escape sequences
+        Some(0x1b) => match (keys.next(), keys.next()) {
+            (Some(b'['), Some(b'A')) => Action::PreviousLine,
+            (Some(b'['), Some(b'B')) => Action::NextLine,
+            (Some(b'['), Some(b'5')) => {
+                let _ = keys.next();
+                Action::PreviousPage
+            },
+            (Some(b'['), Some(b'6')) => {
+                let _ = keys.next();
+                Action::NextPage
+            },
+            _ => Action::Ignore,
+        },
+        Some(_) => Action::Ignore,
+    };
+    Some(action)
+}
+
+/// Return the number of columns that `line` takes up on the terminal,
+/// not counting ANSI escape sequences.
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in line.chars() {
+        if in_escape {
+            in_escape = !c.is_ascii_alphabetic();
+        } else if c == '\x1b' {
+            in_escape = true;
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Return the number of terminal rows that each line takes up.
+fn line_rows(lines: &[&str], columns: usize) -> Vec<usize> {
+    lines.iter().map(|line| {
+        let width = visible_width(line);
+        if width == 0 || columns == 0 { 1 } else { (width + columns - 1) / columns }
+    }).collect()
+}
+
+/// Return the end of the window of lines starting at `top` that fits into
+/// `height` rows. At least one line is shown.
+fn window_end(rows: &[usize], top: usize, height: usize) -> usize {
+    let mut used = 0;
+    let mut end = top;
+    while end < rows.len() && (end == top || used + rows[end] <= height) {
+        used += rows[end];
+        end += 1;
+    }
+    end
+}
+
+/// Return the first line of the last window of the output.
+fn last_top(rows: &[usize], height: usize) -> usize {
+    let mut used = 0;
+    let mut top = rows.len();
+    while top > 0 && used + rows[top - 1] <= height {
+        used += rows[top - 1];
+        top -= 1;
+    }
+    if top == rows.len() { top.saturating_sub(1) } else { top }
+}
+
+/// Run `stty` on the terminal and return what it printed.
+fn stty(args: &[&str]) -> Option<String> {
+    Command::new("stty").args(args)
+                        .stdin(Stdio::inherit())
+                        .stderr(Stdio::null())
+                        .output()
+                        .ok()
+                        .filter(|output| output.status.success())
+                        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The terminal in single key mode, restored when dropped.
+struct KeyMode {
+    saved: String,
+}
+
+impl KeyMode {
+    /// Switch the terminal to reading single key presses without echo and
+    /// without signals, or return `None` if that is not possible.
+    fn enable() -> Option<KeyMode> {
+        let saved = match stty(&["-g"]) {
+            Some(saved) => saved,
+            None => return None,
+        };
+        stty(&["-icanon", "-echo", "-isig", "min", "1", "time", "0"]).map(|_| KeyMode { saved: saved })
+    }
+}
+
+impl Drop for KeyMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+/// Show `lines` one screen of `height` rows at a time, until the user
+/// quits or pages past the end.
+fn run_builtin(lines: &[&str], columns: usize, height: usize) -> io::Result<()> {
+    let _key_mode = match KeyMode::enable() {
+        Some(key_mode) => key_mode,
+        None => return write_all(lines),
+    };
+    let rows = line_rows(lines, columns);
+    let stdin = io::stdin();
+    let mut keys = stdin.lock().bytes().filter_map(|key| key.ok());
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut top = 0;
+    loop {
+        let end = window_end(&rows, top, height);
+        try!(write!(out, "\x1b[H\x1b[2J"));
+        for line in &lines[top..end] {
+            try!(writeln!(out, "{}", line));
+        }
+        try!(write!(out, "\x1b[7m lines {}-{} of {} (space: next page, b: back, q: quit) \x1b[0m",
+                    top + 1, end, lines.len()));
+        try!(out.flush());
+
+        top = match read_action(&mut keys) {
+            None | Some(Action::Quit) => break,
+            Some(Action::NextPage) if end == lines.len() => break,
+            Some(Action::NextPage) => end,
+            Some(Action::PreviousPage) => top.saturating_sub(height),
+            Some(Action::NextLine) if end < lines.len() => top + 1,
+            Some(Action::PreviousLine) => top.saturating_sub(1),
+            Some(Action::Top) => 0,
+            Some(Action::Bottom) => last_top(&rows, height),
+            Some(_) => top,
+        };
+    }
+    // Remove the status line, leaving the last screen in place
+    try!(write!(out, "\r\x1b[K"));
+    out.flush()
+}
+
+/// Pipe `output` through the command in `$PAGER`. Return `false` if it
+/// could not be started.
+fn run_pager(command: &[String], output: &[u8]) -> bool {
+    let mut child = match Command::new(&command[0]).args(&command[1..]).stdin(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            debug!("Could not run pager {}: {}", command[0], e);
+            return false;
+        },
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager may quit before reading everything
+        let _ = stdin.write_all(output);
+    }
+    let _ = child.wait();
+    true
+}
+
+fn write_all(lines: &[&str]) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for line in lines {
+        try!(writeln!(out, "{}", line));
+    }
+    out.flush()
+}
+
+/// Return whether output to stdout can be paged, i.e. whether both stdout
+/// and stdin are terminals.
+pub fn is_available() -> bool {
+    term_size::dimensions_stdout().is_some() && term_size::dimensions_stdin().is_some()
+}
+
+/// Return whether paging was turned off by setting `$TLDR_PAGER` to `0`.
+pub fn is_disabled() -> bool {
+    env::var_os("TLDR_PAGER").map_or(false, |value| value == "0")
+}
+
+/// Write `output` to stdout, through a pager if it is taller than the
+/// terminal.
+pub fn page(output: &[u8]) -> io::Result<()> {
+    let text = String::from_utf8_lossy(output);
+    let lines = text.lines().collect::<Vec<_>>();
+    let (columns, height) = match term_size::dimensions_stdout() {
+        Some(dimensions) => dimensions,
+        None => return write_all(&lines),
+    };
+    if line_rows(&lines, columns).iter().sum::<usize>() < height {
+        return write_all(&lines);
+    }
+    let pager = env::var("PAGER").map(|command| split_command(&command)).unwrap_or_default();
+    if !pager.is_empty() && run_pager(&pager, output) {
+        return Ok(());
+    }
+    // Keep one row for the status line
+    run_builtin(&lines, columns, height.saturating_sub(1).max(1))
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+
+    use super::{is_disabled, last_top, line_rows, read_action, visible_width, window_end, Action};
+
+    #[test]
+    fn test_read_action() {
+        let mut keys = b" q\x1b[B\x1b[6~x".iter().cloned();
+        assert_eq!(read_action(&mut keys), Some(Action::NextPage));
+        assert_eq!(read_action(&mut keys), Some(Action::Quit));
+        assert_eq!(read_action(&mut keys), Some(Action::NextLine));
+        assert_eq!(read_action(&mut keys), Some(Action::NextPage));
+        assert_eq!(read_action(&mut keys), Some(Action::Ignore));
+        assert_eq!(read_action(&mut keys), None);
+    }
+
+    #[test]
+    fn test_is_disabled() {
+        env::set_var("TLDR_PAGER", "0");
+        assert!(is_disabled());
+        env::set_var("TLDR_PAGER", "1");
+        assert!(!is_disabled());
+        env::remove_var("TLDR_PAGER");
+        assert!(!is_disabled());
+    }
+
+    #[test]
+    fn test_visible_width() {
+        assert_eq!(visible_width("tar xf file"), 11);
+        assert_eq!(visible_width("\x1b[31mtar\x1b[0m xf"), 6);
+        assert_eq!(visible_width(""), 0);
+    }
+
+    #[test]
+    fn test_window() {
+        let rows = line_rows(&["a", "", "bbbbbbbbbbbbbbb", "c", "d"], 10);
+        assert_eq!(rows, vec![1, 1, 2, 1, 1]);
+        assert_eq!(window_end(&rows, 0, 3), 2);
+        assert_eq!(window_end(&rows, 2, 3), 4);
+        assert_eq!(window_end(&rows, 2, 1), 3);
+        assert_eq!(window_end(&rows, 4, 3), 5);
+        assert_eq!(last_top(&rows, 3), 3);
+        assert_eq!(last_top(&rows, 10), 0);
+    }
+}
@@ -0,0 +1,116 @@
+//! Scaffolding for project-local page directories and new pages.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use lookup::is_page_name;
+
+/// Platform subdirectories created inside a project page directory. macOS
+/// pages go into `macos`, as upstream; `osx` is still searched as well.
+const PLATFORM_DIRS: [&'static str; 4] = ["common", "linux", "macos", "sunos"];
+
+/// Name of the example page.
+const EXAMPLE_NAME: &'static str = "example";
+
+/// URL of the upstream page for creating a new common page.
+const NEW_PAGE_URL: &'static str = "https://github.com/tldr-pages/tldr/new/main/pages/common";
+
+/// Return a new page for `name`, following the official page format.
+pub fn page_template(name: &str) -> String {
+    format!("# {name}
+
+> A short description of what the command does.
+> More information: <https://example.com>.
+
+- Describe what the first example does:
+
+`{name} {{{{path/to/file}}}}`
+
+- Describe what the second example does:
+
+`{name} --flag {{{{value}}}}`
+", name = name)
+}
+
+/// Write a new page for `name` from the template to `dir` and return its
+/// path. Fails if the file exists already or `name` is not a valid page
+/// name.
+pub fn create_page(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    if !is_page_name(name) {
+        return Err(format!("Invalid page name: {}", name));
+    }
+    let path = dir.join(format!("{}.md", name));
+    let mut file = try!(
+        OpenOptions::new().write(true).create_new(true).open(&path)
+                          .map_err(|e| format!("Could not create {}: {}", path.display(), e))
+    );
+    try!(file.write_all(page_template(name).as_bytes())
+             .map_err(|e| format!("Could not write {}: {}", path.display(), e)));
+    Ok(path)
+}
+
+/// Percent-encode a string for use in a URL query.
+fn url_encode(value: &str) -> String {
+    value.bytes().map(|byte| match byte {
+        b if (b as char).is_ascii_alphanumeric() || b"-_.~".contains(&b) => (b as char).to_string(),
+        _ => format!("%{:02X}", byte),
+    }).collect()
+}
+
+/// Return the upstream URL for creating the page `name` with `content`.
+pub fn new_page_url(name: &str, content: &str) -> String {
+    format!("{}?filename={}&value={}", NEW_PAGE_URL, url_encode(&format!("{}.md", name)), url_encode(content))
+}
+
+/// Create the `.tldr/pages` layout below `root`, including the platform
+/// directories and an example page. Existing files are left untouched.
+///
+/// Return the path to the created pages directory.
+pub fn init_project(root: &Path) -> Result<PathBuf, String> {
+    let pages_dir = root.join(".tldr").join("pages");
+
+    // Create platform directories
+    for platform in &PLATFORM_DIRS {
+        let dir = pages_dir.join(platform);
+        try!(fs::create_dir_all(&dir).map_err(|e| {
+            format!("Could not create directory {}: {}", dir.display(), e)
+        }));
+    }
+
+    // Write example page, unless one exists already
+    let common_dir = pages_dir.join("common");
+    if !common_dir.join(format!("{}.md", EXAMPLE_NAME)).exists() {
+        let _ = try!(create_page(&common_dir, EXAMPLE_NAME));
+    }
+
+    Ok(pages_dir)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{create_page, page_template, url_encode, new_page_url};
+
+    #[test]
+    fn test_page_template() {
+        let page = page_template("foo");
+        assert!(page.starts_with("# foo\n\n> "));
+        assert!(page.contains("`foo {{path/to/file}}`"));
+    }
+
+    #[test]
+    fn test_new_page_url() {
+        assert_eq!(url_encode("a b/{c}\n"), "a%20b%2F%7Bc%7D%0A");
+        assert_eq!(new_page_url("foo", "# foo\n"),
+                   "https://github.com/tldr-pages/tldr/new/main/pages/common?filename=foo.md&value=%23%20foo%0A");
+    }
+
+    #[test]
+    fn test_create_page_invalid_name() {
+        let dir = Path::new("/nonexistent");
+        assert_eq!(create_page(dir, "../foo"), Err("Invalid page name: ../foo".to_string()));
+        assert_eq!(create_page(dir, "/tmp/foo"), Err("Invalid page name: /tmp/foo".to_string()));
+    }
+}
@@ -0,0 +1,72 @@
+//! Searching the pages in the cache.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use cache::Cache;
+use error::TealdeerError;
+use tokenizer::Tokenizer;
+use types::{LineType, PageMetadata};
+
+/// Return the first description line and the front matter metadata of the
+/// page at `path`.
+fn page_header(path: &Path) -> (Option<String>, PageMetadata) {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            warn!("Could not open {}: {}", path.display(), e);
+            return (None, PageMetadata::default());
+        },
+    };
+    let mut tokenizer = Tokenizer::new(BufReader::new(file));
+    let mut summary = None;
+    while let Some(token) = tokenizer.next_token() {
+        match token {
+            LineType::Description(text) => {
+                summary = Some(text);
+                break;
+            },
+            LineType::ExampleText(_) | LineType::ExampleCode(_) => break,
+            _ => {},
+        }
+    }
+    (summary, tokenizer.metadata().clone())
+}
+
+/// Return the first description line of the page at `path`.
+pub fn page_summary(path: &Path) -> Option<String> {
+    page_header(path).0
+}
+
+/// Return the front matter metadata of the page at `path`.
+pub fn page_metadata(path: &Path) -> PageMetadata {
+    page_header(path).1
+}
+
+/// Find all pages whose name or summary contains `keyword` or which are
+/// tagged with it (case insensitive). If `tag` is set, only pages with that
+/// tag are considered.
+///
+/// Return a list of `(name, summary)` pairs, sorted by page name.
+pub fn apropos(cache: &Cache, keyword: &str, tag: Option<&str>) -> Result<Vec<(String, String)>, TealdeerError> {
+    let keyword = keyword.to_lowercase();
+    let pages = try!(cache.list_page_paths());
+    Ok(pages.into_iter()
+            .filter_map(|(name, path)| {
+                let (summary, metadata) = page_header(&path);
+                let summary = summary.unwrap_or_else(String::new);
+                if let Some(tag) = tag {
+                    if !metadata.tags.iter().any(|t| t == tag) {
+                        return None;
+                    }
+                }
+                if name.to_lowercase().contains(&keyword) || summary.to_lowercase().contains(&keyword) ||
+                        metadata.tags.iter().any(|tag| tag.to_lowercase() == keyword) {
+                    Some((name, summary))
+                } else {
+                    None
+                }
+            })
+            .collect())
+}
@@ -0,0 +1,130 @@
+//! Exporting the cache as a static HTML site.
+
+use std::fs::{self, File};
+use std::io::{BufReader, Write};
+use std::path::Path;
+
+use cache::Cache;
+use error::TealdeerError::{UpdateError, CacheError};
+use html::{encode_path_segment, render_index, render_page};
+use search;
+use tokenizer::Tokenizer;
+
+/// Write `content` to the file at `path`.
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    File::create(path).and_then(|mut file| file.write_all(content.as_bytes()))
+                      .map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+/// Write the pages of all languages and platforms of the cache to
+/// `out_dir`. Each platform directory of each language becomes a section
+/// in `<language>/<platform>`, with one HTML file per page and an
+/// `index.html` of them. The `index.html` in `out_dir` links the sections.
+///
+/// Return the number of exported pages.
+pub fn export_site(cache: &Cache, out_dir: &Path) -> Result<usize, String> {
+    let sections = try!(cache.list_sections().map_err(|e| match e {
+        UpdateError(msg) | CacheError(msg) => msg,
+    }));
+
+    let mut index = vec![];
+    let mut count = 0;
+    for section in &sections {
+        let dir = out_dir.join(&section.language).join(&section.platform);
+        try!(fs::create_dir_all(&dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e)));
+
+        let mut entries = vec![];
+        for (name, path) in &section.pages {
+            let file = try!(File::open(path).map_err(|e| format!("Could not open {}: {}", path.display(), e)));
+            let mut tokenizer = Tokenizer::new(BufReader::new(file));
+            let html = render_page(&mut tokenizer, name, &section.language, Some("index.html"));
+            try!(write_file(&dir.join(format!("{}.html", name)), &html));
+
+            let summary = search::page_summary(path).unwrap_or_default();
+            entries.push((name.clone(), format!("{}.html", encode_path_segment(name)), summary));
+        }
+        let title = format!("tldr pages: {} ({})", section.platform, section.language);
+        let section_index = render_index(&title, &entries, &section.language, Some("../../index.html"));
+        try!(write_file(&dir.join("index.html"), &section_index));
+
+        let href = format!("{}/{}/index.html",
+                           encode_path_segment(&section.language), encode_path_segment(&section.platform));
+        let unit = if section.pages.len() == 1 { "page" } else { "pages" };
+        index.push((format!("{} ({})", section.platform, section.language), href,
+                    format!("{} {}", section.pages.len(), unit)));
+        count += section.pages.len();
+    }
+
+    try!(write_file(&out_dir.join("index.html"), &render_index("tldr pages", &index, "en", None)));
+    Ok(count)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::{self, File};
+    use std::io::{Read, Write};
+    use std::path::Path;
+
+    use tempdir::TempDir;
+
+    use cache::Cache;
+    use types::OsType;
+    use super::export_site;
+
+    fn write_page(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        File::create(path).unwrap().write_all(content.as_bytes()).unwrap();
+    }
+
+    fn read_file(path: &Path) -> String {
+        let mut content = String::new();
+        let _ = File::open(path).unwrap().read_to_string(&mut content).unwrap();
+        content
+    }
+
+    /// Return a cache of the pages below `root`.
+    fn test_cache(root: &Path) -> Cache {
+        Cache::new("", OsType::Linux, vec![]).with_dirs(&root.join("pages"), &root.join("data"))
+    }
+
+    #[test]
+    fn test_export_site() {
+        let root = TempDir::new("tealdeer-site").unwrap();
+        write_page(&root.path().join("pages/common/tar.md"), "# tar\n\n> Archiving utility.\n");
+        write_page(&root.path().join("pages/osx/pbcopy.md"), "# pbcopy\n\n> Copy to the clipboard.\n");
+        write_page(&root.path().join("pages.de/common/tar.md"), "# tar\n\n> Archivierungsprogramm.\n");
+        write_page(&root.path().join("data/overlay/en/common/tar.md"), "# tar\n\n> Edited.\n");
+        let cache = test_cache(root.path());
+        let out = root.path().join("site");
+
+        // All languages and platforms are exported, not only the current ones
+        assert_eq!(export_site(&cache, &out).unwrap(), 3);
+        let index = read_file(&out.join("index.html"));
+        assert!(index.contains("<a href=\"en/common/index.html\">common (en)</a> \u{2014} 1 page"));
+        assert!(index.contains("<a href=\"en/osx/index.html\">osx (en)</a>"));
+        assert!(index.contains("<a href=\"de/common/index.html\">common (de)</a>"));
+
+        let section = read_file(&out.join("de/common/index.html"));
+        assert!(section.contains("<html lang=\"de\">"));
+        assert!(section.contains("<nav><a href=\"../../index.html\">All pages</a></nav>"));
+        assert!(section.contains("<a href=\"tar.html\">tar</a> \u{2014} Archivierungsprogramm."));
+
+        let page = read_file(&out.join("de/common/tar.html"));
+        assert!(page.contains("<html lang=\"de\">"));
+        assert!(page.contains("<nav><a href=\"index.html\">All pages</a></nav>"));
+        assert!(read_file(&out.join("en/common/tar.html")).contains("<p>Edited.</p>"));
+    }
+
+    #[test]
+    fn test_export_site_links() {
+        let root = TempDir::new("tealdeer-site").unwrap();
+        write_page(&root.path().join("pages/common/c# 100%?.md"), "# c# 100%?\n\n> Odd name.\n");
+        let cache = test_cache(root.path());
+        let out = root.path().join("site");
+
+        assert_eq!(export_site(&cache, &out).unwrap(), 1);
+        assert!(out.join("en/common/c# 100%?.html").is_file());
+        let section = read_file(&out.join("en/common/index.html"));
+        assert!(section.contains("<a href=\"c%23%20100%25%3F.html\">c# 100%?</a>"));
+    }
+}
@@ -0,0 +1,88 @@
+//! Opt-in tracking of how often pages are viewed.
+//!
+//! The counts are stored in a `views` file in the data directory, one
+//! `<count> <name>` pair per line.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use collate;
+use config;
+
+/// Counter for the names of temporary files written by this process.
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Return the path to the view count file.
+fn stats_path() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("views"))
+}
+
+/// Return whether view tracking was enabled through `$TLDR_TRACK_VIEWS`.
+pub fn enabled() -> bool {
+    config::flag("TLDR_TRACK_VIEWS")
+}
+
+/// Read the view counts.
+pub fn read_counts() -> Result<BTreeMap<String, u64>, String> {
+    let mut counts = BTreeMap::new();
+    let path = match stats_path() {
+        Some(path) => path,
+        None => return Ok(counts),
+    };
+    if !path.exists() {
+        return Ok(counts);
+    }
+    let file = try!(File::open(&path).map_err(|e| format!("Could not open {}: {}", path.display(), e)));
+    for line in BufReader::new(file).lines() {
+        let line = try!(line.map_err(|e| format!("Could not read {}: {}", path.display(), e)));
+        let mut parts = line.trim().splitn(2, ' ');
+        if let (Some(count), Some(name)) = (parts.next(), parts.next()) {
+            if let Ok(count) = count.parse::<u64>() {
+                let _ = counts.insert(name.to_string(), count);
+            }
+        }
+    }
+    Ok(counts)
+}
+
+/// Increment the view count of a page.
+pub fn record_view(name: &str) -> Result<(), String> {
+    let path = try!(stats_path().ok_or_else(|| "Could not determine data directory".to_string()));
+    let mut counts = try!(read_counts());
+    *counts.entry(name.to_string()).or_insert(0) += 1;
+
+    // Write to a temporary file first, so that concurrent readers never see
+    // a partially written file. Each writer uses a file of its own, so that
+    // concurrent writers don't write into the same one.
+    if let Some(dir) = path.parent() {
+        try!(fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e)));
+    }
+    let tmp_path = path.with_extension(format!("{}.{}.tmp", process::id(), TMP_COUNTER.fetch_add(1, Ordering::SeqCst)));
+    let result = OpenOptions::new().write(true).create_new(true).open(&tmp_path)
+        .map_err(|e| format!("Could not create {}: {}", tmp_path.display(), e))
+        .and_then(|mut file| {
+            for (name, count) in &counts {
+                try!(writeln!(file, "{} {}", count, name)
+                         .map_err(|e| format!("Could not write {}: {}", tmp_path.display(), e)));
+            }
+            Ok(())
+        })
+        .and_then(|_| fs::rename(&tmp_path, &path).map_err(|e| format!("Could not write {}: {}", path.display(), e)));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Return the `limit` most viewed pages with their view counts, most
+/// viewed first.
+pub fn top(limit: usize) -> Result<Vec<(String, u64)>, String> {
+    let mut pages: Vec<(String, u64)> = try!(read_counts()).into_iter().collect();
+    pages.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| collate::natural_cmp(&a.0, &b.0)));
+    pages.truncate(limit);
+    Ok(pages)
+}
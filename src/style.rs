@@ -0,0 +1,366 @@
+//! Styles used when printing pages, configurable through `$TLDR_COLORS`.
+//!
+//! The variable contains colon separated `element=colour` pairs, e.g.
+//! `example=green:command=#5fafd7:variable=208`. Colours may be given by
+//! name, as a 256-colour palette index or as `#rrggbb`. Hex colours are
+//! downgraded to what the terminal supports. A colour can be combined with
+//! the attributes `bold`, `italic`, `underline` and `dim` using `+`, e.g.
+//! `variable=underline+yellow`, or an element can use attributes only.
+//! On terminals without colour support (`TERM=dumb` or unset), all styling
+//! is disabled.
+//!
+//! With `highlight` set (by `$TLDR_HIGHLIGHT` in the `tldr` binary),
+//! example commands are highlighted according to their shell syntax, using
+//! the additional elements `flag`, `string`, `operator` and `subcommand`.
+//!
+//! The bullet in front of examples and the prefix of example commands are
+//! configured through `$TLDR_BULLET` and `$TLDR_COMMAND_PREFIX`. Either may
+//! be set to an empty string to print nothing.
+
+use std::env;
+
+use ansi_term::{Colour, Style};
+
+/// The colour capabilities of a terminal.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ColourSupport {
+    /// No colours or text attributes at all (e.g. `TERM=dumb`).
+    NoColour,
+    /// 24-bit colours.
+    TrueColour,
+    /// The 256-colour xterm palette.
+    Palette256,
+    /// The 8 basic colours plus their bright (bold) variants.
+    Palette16,
+}
+
+impl ColourSupport {
+    /// Determine the colour support of the terminal from `$COLORTERM` and `$TERM`.
+    ///
+    /// If `$TERM` is unset or `dumb`, the terminal is assumed not to support
+    /// any styling.
+    pub fn detect() -> ColourSupport {
+        let colorterm = env::var("COLORTERM").unwrap_or_else(|_| String::new());
+        let term = env::var("TERM").unwrap_or_else(|_| String::new());
+        if term.is_empty() || term == "dumb" {
+            ColourSupport::NoColour
+        } else if colorterm == "truecolor" || colorterm == "24bit" {
+            ColourSupport::TrueColour
+        } else if term.contains("256color") {
+            ColourSupport::Palette256
+        } else {
+            ColourSupport::Palette16
+        }
+    }
+}
+
+/// The styles of the page elements.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Styles {
+    /// The description of the command, and tables and headings.
+    pub description: Style,
+    /// The descriptions of examples and list items.
+    pub example_text: Style,
+    /// Example commands.
+    pub example_code: Style,
+    /// `{{placeholders}}` in example commands.
+    pub example_variable: Style,
+    /// Flags like `-v` in highlighted example commands.
+    pub example_flag: Style,
+    /// Quoted strings in highlighted example commands.
+    pub example_string: Style,
+    /// Pipes, redirections and other operators in highlighted example
+    /// commands.
+    pub example_operator: Style,
+    /// Subcommands like `commit` in highlighted example commands.
+    pub example_subcommand: Style,
+    /// Whether example commands are highlighted according to their shell
+    /// syntax, or only their placeholders.
+    pub highlight: bool,
+    /// Printed in front of example descriptions and list items.
+    pub bullet: String,
+    /// Printed in front of example commands.
+    pub command_prefix: String,
+}
+
+impl Default for Styles {
+    fn default() -> Styles {
+        Styles {
+            description: Style::new(),
+            example_text: Colour::Green.normal(),
+            example_code: Colour::Cyan.normal(),
+            example_variable: Colour::Cyan.underline(),
+            example_flag: Colour::Yellow.normal(),
+            example_string: Colour::Purple.normal(),
+            example_operator: Colour::Red.normal(),
+            example_subcommand: Colour::Cyan.bold(),
+            highlight: false,
+            bullet: "-".into(),
+            command_prefix: String::new(),
+        }
+    }
+}
+
+impl Styles {
+    /// Return styles without any colours or text attributes.
+    pub fn plain() -> Styles {
+        Styles {
+            description: Style::new(),
+            example_text: Style::new(),
+            example_code: Style::new(),
+            example_variable: Style::new(),
+            example_flag: Style::new(),
+            example_string: Style::new(),
+            example_operator: Style::new(),
+            example_subcommand: Style::new(),
+            ..Styles::default()
+        }
+    }
+
+    /// Return a copy of these styles without colours or text attributes,
+    /// keeping the bullet and command prefix.
+    pub fn without_colours(&self) -> Styles {
+        Styles {
+            highlight: self.highlight,
+            bullet: self.bullet.clone(),
+            command_prefix: self.command_prefix.clone(),
+            ..Styles::plain()
+        }
+    }
+
+    /// Return the default styles, overridden by the contents of `$TLDR_COLORS`.
+    ///
+    /// On terminals without colour support, plain styles are returned.
+    pub fn from_env() -> Result<Styles, String> {
+        let support = ColourSupport::detect();
+        let mut styles = match env::var("TLDR_COLORS") {
+            _ if support == ColourSupport::NoColour => Styles::plain(),
+            Ok(spec) => try!(Styles::parse(&spec, support)
+                                    .map_err(|msg| format!("Invalid $TLDR_COLORS: {}", msg))),
+            Err(_) => Styles::default(),
+        };
+        if let Ok(bullet) = env::var("TLDR_BULLET") {
+            styles.bullet = bullet;
+        }
+        if let Ok(prefix) = env::var("TLDR_COMMAND_PREFIX") {
+            styles.command_prefix = prefix;
+        }
+        Ok(styles)
+    }
+
+    /// Parse a colour specification, starting from the default styles.
+    pub fn parse(spec: &str, support: ColourSupport) -> Result<Styles, String> {
+        let mut styles = Styles::default();
+        for pair in spec.split(':').filter(|pair| !pair.is_empty()) {
+            let mut parts = pair.splitn(2, '=');
+            let element = parts.next().unwrap_or("").trim();
+            let value = try!(parts.next().ok_or_else(|| format!("Missing colour for '{}'", element)));
+            let style = try!(parse_style(value.trim(), support));
+            match element {
+                "description" => styles.description = style,
+                "example" => styles.example_text = style,
+                "command" => styles.example_code = style,
+                "variable" => styles.example_variable = style,
+                "flag" => styles.example_flag = style,
+                "string" => styles.example_string = style,
+                "operator" => styles.example_operator = style,
+                "subcommand" => styles.example_subcommand = style,
+                _ => return Err(format!("Unknown element '{}'", element)),
+            }
+        }
+        Ok(styles)
+    }
+}
+
+/// Parse the style of an element: a colour and/or text attributes (`bold`,
+/// `italic`, `underline`, `dim`), joined by `+`.
+fn parse_style(value: &str, support: ColourSupport) -> Result<Style, String> {
+    let mut colour = None;
+    let mut attributes = vec![];
+    for part in value.split('+').map(|part| part.trim()) {
+        match part {
+            "bold" | "italic" | "underline" | "dim" => attributes.push(part),
+            _ if colour.is_some() => return Err(format!("More than one colour in '{}'", value)),
+            _ => colour = Some(try!(parse_colour(part, support))),
+        }
+    }
+    let style = colour.unwrap_or_else(Style::new);
+    Ok(attributes.into_iter().fold(style, |style, attribute| match attribute {
+        "bold" => style.bold(),
+        "italic" => style.italic(),
+        "underline" => style.underline(),
+        _ => style.dimmed(),
+    }))
+}
+
+/// Parse a single colour into a style.
+fn parse_colour(value: &str, support: ColourSupport) -> Result<Style, String> {
+    if value.starts_with('#') {
+        let rgb = try!(parse_hex(value));
+        return Ok(rgb_style(rgb, support));
+    }
+    let colour = match value {
+        "black" => Colour::Black,
+        "red" => Colour::Red,
+        "green" => Colour::Green,
+        "yellow" => Colour::Yellow,
+        "blue" => Colour::Blue,
+        "purple" | "magenta" => Colour::Purple,
+        "cyan" => Colour::Cyan,
+        "white" => Colour::White,
+        _ => match value.parse::<u8>() {
+            Ok(num) => Colour::Fixed(num),
+            Err(_) => return Err(format!("Unknown colour '{}'", value)),
+        },
+    };
+    Ok(colour.normal())
+}
+
+/// Parse a `#rrggbb` colour.
+fn parse_hex(value: &str) -> Result<(u8, u8, u8), String> {
+    let hex = &value[1..];
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+    if hex.len() != 6 || !hex.is_char_boundary(2) || !hex.is_char_boundary(4) {
+        return Err(format!("Invalid hex colour '{}'", value));
+    }
+    match (channel(0), channel(2), channel(4)) {
+        (Ok(r), Ok(g), Ok(b)) => Ok((r, g, b)),
+        _ => Err(format!("Invalid hex colour '{}'", value)),
+    }
+}
+
+/// Return a style for an RGB colour, quantized to the supported palette.
+fn rgb_style(rgb: (u8, u8, u8), support: ColourSupport) -> Style {
+    match support {
+        ColourSupport::NoColour => Style::new(),
+        ColourSupport::TrueColour => Colour::RGB(rgb.0, rgb.1, rgb.2).normal(),
+        ColourSupport::Palette256 => Colour::Fixed(quantize_256(rgb)).normal(),
+        ColourSupport::Palette16 => {
+            let index = quantize_16(rgb);
+            let colour = basic_colour(index % 8);
+            if index >= 8 { colour.bold() } else { colour.normal() }
+        },
+    }
+}
+
+/// The xterm default values of the 16 basic colours.
+const PALETTE_16: [(u8, u8, u8); 16] = [
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0),
+    (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0),
+    (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+];
+
+/// The channel levels of the 6x6x6 colour cube in the 256-colour palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Squared euclidean distance between two colours.
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32) * (x as i32 - y as i32);
+    (d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)) as u32
+}
+
+/// Return the index of the closest colour in the 256-colour palette,
+/// considering the colour cube and the greyscale ramp.
+fn quantize_256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |channel: u8| -> usize {
+        (0..CUBE_LEVELS.len()).min_by_key(|&i| (CUBE_LEVELS[i] as i32 - channel as i32).abs())
+                              .unwrap_or(0)
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube_index = 16 + 36 * r + 6 * g + b;
+    let cube_rgb = (CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+
+    // Greyscale ramp from 8 to 238 in steps of 10
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let grey = if average < 8 { 0 } else { ((average - 8 + 5) / 10).min(23) };
+    let grey_value = (8 + 10 * grey) as u8;
+    let grey_index = 232 + grey as usize;
+
+    if distance(rgb, (grey_value, grey_value, grey_value)) < distance(rgb, cube_rgb) {
+        grey_index as u8
+    } else {
+        cube_index as u8
+    }
+}
+
+/// Return the index of the closest of the 16 basic colours.
+fn quantize_16(rgb: (u8, u8, u8)) -> usize {
+    (0..PALETTE_16.len()).min_by_key(|&i| distance(rgb, PALETTE_16[i])).unwrap_or(0)
+}
+
+/// Return one of the 8 basic colours by index.
+fn basic_colour(index: usize) -> Colour {
+    match index {
+        0 => Colour::Black,
+        1 => Colour::Red,
+        2 => Colour::Green,
+        3 => Colour::Yellow,
+        4 => Colour::Blue,
+        5 => Colour::Purple,
+        6 => Colour::Cyan,
+        _ => Colour::White,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ansi_term::{Colour, Style};
+
+    use super::{Styles, ColourSupport, quantize_256, quantize_16};
+
+    #[test]
+    fn test_quantize_256() {
+        assert_eq!(quantize_256((0, 0, 0)), 16);
+        assert_eq!(quantize_256((255, 255, 255)), 231);
+        assert_eq!(quantize_256((255, 0, 0)), 196);
+        assert_eq!(quantize_256((95, 175, 215)), 74);
+        assert_eq!(quantize_256((128, 128, 128)), 244);
+    }
+
+    #[test]
+    fn test_quantize_16() {
+        assert_eq!(quantize_16((10, 0, 0)), 0);
+        assert_eq!(quantize_16((200, 10, 10)), 1);
+        assert_eq!(quantize_16((250, 250, 250)), 15);
+    }
+
+    #[test]
+    fn test_parse_styles() {
+        let styles = Styles::parse("example=red:command=#ff0000:variable=208",
+                                   ColourSupport::Palette256).unwrap();
+        assert_eq!(styles.example_text, Colour::Red.normal());
+        assert_eq!(styles.example_code, Colour::Fixed(196).normal());
+        assert_eq!(styles.example_variable, Colour::Fixed(208).normal());
+        assert_eq!(styles.description, Styles::default().description);
+
+        let styles = Styles::parse("command=#00ff00", ColourSupport::TrueColour).unwrap();
+        assert_eq!(styles.example_code, Colour::RGB(0, 255, 0).normal());
+
+        let styles = Styles::parse("command=#00ff00", ColourSupport::Palette16).unwrap();
+        assert_eq!(styles.example_code, Colour::Green.bold());
+    }
+
+    #[test]
+    fn test_parse_styles_attributes() {
+        let styles = Styles::parse("description=bold:example=italic+green:variable=#ff0000+underline+dim",
+                                   ColourSupport::Palette256).unwrap();
+        assert_eq!(styles.description, Style::new().bold());
+        assert_eq!(styles.example_text, Colour::Green.italic());
+        assert_eq!(styles.example_variable, Colour::Fixed(196).underline().dimmed());
+
+        let styles = Styles::parse("flag=blue:subcommand=bold", ColourSupport::Palette16).unwrap();
+        assert_eq!(styles.example_flag, Colour::Blue.normal());
+        assert_eq!(styles.example_subcommand, Style::new().bold());
+    }
+
+    #[test]
+    fn test_parse_styles_invalid() {
+        assert!(Styles::parse("title=red", ColourSupport::Palette16).is_err());
+        assert!(Styles::parse("command", ColourSupport::Palette16).is_err());
+        assert!(Styles::parse("command=#ff00", ColourSupport::Palette16).is_err());
+        assert!(Styles::parse("command=mauve", ColourSupport::Palette16).is_err());
+        assert!(Styles::parse("command=red+blue", ColourSupport::Palette16).is_err());
+        assert!(Styles::parse("command=bold+blink", ColourSupport::Palette16).is_err());
+    }
+}
@@ -2,29 +2,169 @@
 
 use std::io::BufRead;
 
-use types::LineType;
+use types::{LineType, PageMetadata};
 
+/// Splits a page into `LineType` tokens, one per line.
 #[derive(Debug)]
 pub struct Tokenizer<R: BufRead> {
     reader: R,
     current_line: String,
+    first_line: bool,
+    metadata: PageMetadata,
 }
 
 impl<R> Tokenizer<R> where R: BufRead {
+    /// Create a tokenizer for the page read from `reader`.
     pub fn new(reader: R) -> Tokenizer<R> {
         Tokenizer {
             reader: reader,
             current_line: String::new(),
+            first_line: true,
+            metadata: PageMetadata::default(),
         }
     }
 
+    /// Return the metadata from the front matter of the page. It is only
+    /// available once the first token has been read.
+    pub fn metadata(&self) -> &PageMetadata {
+        &self.metadata
+    }
+
+    /// Return the next line of the page, or `None` at the end of it. The
+    /// front matter is skipped.
     pub fn next_token(&mut self) -> Option<LineType> {
         self.current_line.clear();
         let bytes_read = self.reader.read_line(&mut self.current_line);
         match bytes_read {
             Ok(0) => None,
             Err(e) => { warn!("Could not read line from token reader: {:?}", e); None},
-            Ok(_) => Some(LineType::from(&self.current_line[..])),
+            Ok(_) => {
+                // A page may start with a YAML (---) or TOML (+++) front
+                // matter block, which is not rendered.
+                if self.first_line {
+                    self.first_line = false;
+                    let delimiter = self.current_line.trim().to_string();
+                    if delimiter == "---" || delimiter == "+++" {
+                        self.read_front_matter(&delimiter);
+                        return self.next_token();
+                    }
+                }
+                Some(LineType::from(&self.current_line[..]))
+            },
+        }
+    }
+
+    /// Read the front matter block up to the closing `delimiter`.
+    fn read_front_matter(&mut self, delimiter: &str) {
+        let mut lines = vec![];
+        loop {
+            self.current_line.clear();
+            match self.reader.read_line(&mut self.current_line) {
+                Ok(0) => break,
+                Err(e) => { warn!("Could not read front matter: {:?}", e); break },
+                Ok(_) => {
+                    if self.current_line.trim() == delimiter {
+                        break;
+                    }
+                    lines.push(self.current_line.clone());
+                },
+            }
+        }
+        self.metadata = parse_front_matter(&lines, delimiter == "+++");
+    }
+}
+
+/// Parse a scalar value, removing surrounding quotes.
+fn parse_scalar(value: &str) -> String {
+    value.trim().trim_matches(|c| c == '"' || c == '\'').to_string()
+}
+
+/// Parse a value into a list. Both inline lists (`[a, b]`) and comma
+/// separated scalars are accepted.
+fn parse_list(value: &str) -> Vec<String> {
+    let value = value.trim();
+    let inner = if value.starts_with('[') && value.ends_with(']') {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    };
+    inner.split(',').map(parse_scalar).filter(|item| !item.is_empty()).collect()
+}
+
+/// Parse the lines of a front matter block. Only the subset of YAML and
+/// TOML that is needed for flat keys, strings and lists is supported.
+fn parse_front_matter(lines: &[String], toml: bool) -> PageMetadata {
+    let separator = if toml { '=' } else { ':' };
+    let mut metadata = PageMetadata::default();
+    let mut current_key = String::new();
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        // Items of a YAML block list belong to the previous key
+        if !toml && trimmed.starts_with("- ") {
+            let item = parse_scalar(&trimmed[2..]);
+            match &current_key[..] {
+                "tags" => metadata.tags.push(item),
+                "related" => metadata.related.push(item),
+                _ => {},
+            }
+            continue;
+        }
+
+        let mut parts = trimmed.splitn(2, separator);
+        let key = parts.next().unwrap_or("").trim().to_string();
+        let value = parts.next().unwrap_or("").trim();
+        match &key[..] {
+            "author" => metadata.author = Some(parse_scalar(value)),
+            "tags" => metadata.tags.extend(parse_list(value)),
+            "related" => metadata.related.extend(parse_list(value)),
+            _ => debug!("Ignoring front matter key {:?}", key),
+        }
+        current_key = key;
+    }
+    metadata
+}
+
+#[cfg(test)]
+mod test {
+    use super::Tokenizer;
+    use types::{LineType, PageMetadata};
+
+    fn tokenize(page: &str) -> (Vec<LineType>, PageMetadata) {
+        let mut tokenizer = Tokenizer::new(page.as_bytes());
+        let mut tokens = vec![];
+        while let Some(token) = tokenizer.next_token() {
+            tokens.push(token);
         }
+        (tokens, tokenizer.metadata().clone())
+    }
+
+    #[test]
+    fn test_yaml_front_matter() {
+        let (tokens, metadata) = tokenize("---\nauthor: Jane Doe\ntags: [deploy, 'ops']\nrelated:\n  - kubectl\n  - helm\n---\n# deploy\n");
+        assert_eq!(tokens, vec![LineType::Title("deploy".into())]);
+        assert_eq!(metadata, PageMetadata {
+            author: Some("Jane Doe".into()),
+            tags: vec!["deploy".into(), "ops".into()],
+            related: vec!["kubectl".into(), "helm".into()],
+        });
+    }
+
+    #[test]
+    fn test_toml_front_matter() {
+        let (tokens, metadata) = tokenize("+++\nauthor = \"Jane\"\ntags = [\"net\"]\n+++\n> Description\n");
+        assert_eq!(tokens, vec![LineType::Description("Description".into())]);
+        assert_eq!(metadata.author, Some("Jane".into()));
+        assert_eq!(metadata.tags, vec!["net".to_string()]);
+    }
+
+    #[test]
+    fn test_no_front_matter() {
+        let (tokens, metadata) = tokenize("# tar\n---\n");
+        assert_eq!(tokens, vec![LineType::Title("tar".into()), LineType::ExampleText("".into())]);
+        assert_eq!(metadata, PageMetadata::default());
     }
 }
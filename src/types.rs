@@ -1,55 +1,145 @@
 //! Types used in the client.
 
-use rustc_serialize::{Decodable, Decoder};
+use std::str::FromStr;
 
+#[cfg(feature = "cli")] use rustc_serialize::{Decodable, Decoder};
 
+
+/// The operating system whose platform specific pages are shown.
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 #[allow(dead_code)]
 pub enum OsType {
+    /// Linux, with the pages in `linux`.
     Linux,
+    /// macOS, with the pages in `osx` or `macos`.
     OsX,
+    /// SunOS, with the pages in `sunos`.
     SunOs,
+    /// Any other system, which only gets the common pages.
     Other,
 }
 
 
+impl FromStr for OsType {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, String> {
+        let lowercase = input.to_lowercase();
+        match &lowercase[..] {
+            "linux" => Ok(OsType::Linux),
+            "osx" | "macos" => Ok(OsType::OsX),
+            "sunos" => Ok(OsType::SunOs),
+            "other" => Ok(OsType::Other),
+            _ => Err(format!("Invalid OS type: '{}'. Choose one of 'linux', \
+                              'macos' (or 'osx'), 'sunos' or 'other'.", lowercase))
+        }
+    }
+}
+
+
 /// Custom Decodable implementation, so that we can parse command line arguments
 /// directly into an `OsType` instance.
+#[cfg(feature = "cli")]
 impl Decodable for OsType {
     fn decode<D: Decoder>(d: &mut D) -> Result<Self, D::Error> {
-        d.read_str().and_then(|input| {
-            let lowercase = input.to_lowercase();
-            match &lowercase[..] {
-                "linux" => Ok(OsType::Linux),
-                "osx" => Ok(OsType::OsX),
-                "sunos" => Ok(OsType::SunOs),
-                "other" => Ok(OsType::Other),
-                _ => Err(d.error(&format!("Invalid OS type: '{}'. Choose one of 'linux', \
-                                           'osx', 'sunos' or 'other'.", lowercase)))
-            }
-        })
+        d.read_str().and_then(|input| input.parse().map_err(|msg: String| d.error(&msg)))
     }
 }
 
 
 
+/// Metadata from the optional front matter block of a page.
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "cli", derive(RustcEncodable))]
+pub struct PageMetadata {
+    /// The author of the page, if given.
+    pub author: Option<String>,
+    /// Tags for grouping pages, like `deploy`.
+    pub tags: Vec<String>,
+    /// Names of related pages.
+    pub related: Vec<String>,
+}
+
+/// Information about a page in the cache.
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(RustcEncodable))]
+pub struct PageInfo {
+    /// The name of the page, i.e. of the command.
+    pub name: String,
+    /// The platform directory the page is in, like `common` or `linux`.
+    pub platform: String,
+    /// The language of the page, like `en`.
+    pub language: String,
+    /// The path to the page file.
+    pub path: String,
+    /// The author from the front matter of the page.
+    pub author: Option<String>,
+    /// The tags from the front matter of the page.
+    pub tags: Vec<String>,
+    /// The related pages from the front matter of the page.
+    pub related: Vec<String>,
+}
+
+/// A line of a page.
 #[derive(Debug, Eq, PartialEq)]
 pub enum LineType {
+    /// An empty line.
     Empty,
+    /// The `#` title, i.e. the name of the command.
     Title(String),
+    /// A `>` line of the description.
     Description(String),
+    /// The `-` description of an example.
     ExampleText(String),
+    /// The `` ` `` quoted command of an example.
     ExampleCode(String),
+    /// A `##` heading, for custom pages with sections.
+    Heading(String),
+    /// An indented list item below an example, with its nesting depth
+    /// (starting at 1).
+    ListItem(usize, String),
+    /// A row of a `|` separated table. Separator rows (`|---|---|`) contain
+    /// cells of dashes.
+    TableRow(Vec<String>),
+    /// Any other line, which is not shown.
     Other(String),
 }
 
+/// Split a table row like `| a | b |` into its trimmed cells.
+fn table_cells(row: &str) -> Vec<String> {
+    let row = row.trim_start_matches('|');
+    let row = row.strip_suffix('|').unwrap_or(row);
+    row.split('|').map(|cell| cell.trim().to_string()).collect()
+}
+
+/// Return whether the cells of a table row form a separator row like
+/// `|---|:--:|`.
+pub fn is_table_separator(cells: &[String]) -> bool {
+    !cells.is_empty() && cells.iter().all(|cell| {
+        cell.contains('-') && cell.chars().all(|c| c == '-' || c == ':')
+    })
+}
+
+/// Return the nesting depth of a list item indented by `indent`: two
+/// spaces (or a tab) per level.
+fn list_depth(indent: &str) -> usize {
+    let width = indent.chars().map(|c| if c == '\t' { 2 } else { 1 }).sum::<usize>();
+    ::std::cmp::max(1, width / 2)
+}
+
 impl<'a> From<&'a str> for LineType {
     /// Convert a string slice to a LineType. Newlines and whitespace are trimmed.
     fn from(line: &'a str) -> LineType {
         let trimmed = line.trim();
+        let indent = &line[..line.len() - line.trim_start().len()];
+        if !indent.is_empty() && (trimmed.starts_with("- ") || trimmed.starts_with("* ")) {
+            return LineType::ListItem(list_depth(indent), trimmed[2..].trim().into());
+        }
         let mut chars = trimmed.chars();
         match chars.next() {
             None => LineType::Empty,
+            Some('#') if trimmed.starts_with("##") => LineType::Heading(trimmed.trim_left_matches(|chr: char| chr == '#' || chr.is_whitespace()).into()),
+            Some('|') => LineType::TableRow(table_cells(trimmed)),
             Some('#') => LineType::Title(trimmed.trim_left_matches(|chr: char| chr == '#' || chr.is_whitespace()).into()),
             Some('>') => LineType::Description(trimmed.trim_left_matches(|chr: char| chr == '>' || chr.is_whitespace()).into()),
             Some('-') => LineType::ExampleText(trimmed.trim_left_matches(|chr: char| chr == '-' || chr.is_whitespace()).into()),
@@ -61,14 +151,12 @@ impl<'a> From<&'a str> for LineType {
 
 #[cfg(test)]
 mod test {
-    extern crate rustc_serialize;
-    extern crate docopt;
-
     use super::OsType::{self, Linux, OsX, SunOs, Other};
-    use super::LineType;
-    use rustc_serialize::json;
+    use super::{LineType, is_table_separator};
+    #[cfg(feature = "cli")] use rustc_serialize::json;
 
     #[test]
+    #[cfg(feature = "cli")]
     fn test_os_type_decoding_regular() {
         assert_eq!(json::decode::<OsType>("\"linux\"").unwrap(), Linux);
         assert_eq!(json::decode::<OsType>("\"osx\"").unwrap(), OsX);
@@ -77,12 +165,29 @@ mod test {
     }
 
     #[test]
+    fn test_os_type_from_str() {
+        assert_eq!("linux".parse::<OsType>(), Ok(Linux));
+        assert_eq!("macOS".parse::<OsType>(), Ok(OsX));
+        assert_eq!("sunos".parse::<OsType>(), Ok(SunOs));
+        assert_eq!("other".parse::<OsType>(), Ok(Other));
+        assert!("lindows".parse::<OsType>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_os_type_decoding_macos_alias() {
+        assert_eq!(json::decode::<OsType>("\"macos\"").unwrap(), OsX);
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
     fn test_os_type_decoding_uppercase() {
         assert_eq!(json::decode::<OsType>("\"Linux\"").unwrap(), Linux);
         assert_eq!(json::decode::<OsType>("\"LINUX\"").unwrap(), Linux);
     }
 
     #[test]
+    #[cfg(feature = "cli")]
     fn test_os_type_decoding_unknown() {
         assert!(json::decode::<OsType>("\"lindows\"").is_err());
     }
@@ -98,4 +203,17 @@ mod test {
         assert_eq!(LineType::from("`$ cargo run"), LineType::Other("`$ cargo run".into()));
         assert_eq!(LineType::from("jkl\u{f6}"), LineType::Other("jkl\u{f6}".into()));
     }
+
+    #[test]
+    fn test_linetype_from_str_extended() {
+        assert_eq!(LineType::from("## Deploying"), LineType::Heading("Deploying".into()));
+        assert_eq!(LineType::from("  - nested item"), LineType::ListItem(1, "nested item".into()));
+        assert_eq!(LineType::from("    * deeper"), LineType::ListItem(2, "deeper".into()));
+        assert_eq!(LineType::from("\t- tabbed"), LineType::ListItem(1, "tabbed".into()));
+        assert_eq!(LineType::from("| a | b |"), LineType::TableRow(vec!["a".into(), "b".into()]));
+        assert_eq!(LineType::from("|---|:-:|"), LineType::TableRow(vec!["---".into(), ":-:".into()]));
+        assert_eq!(LineType::from("| a | |"), LineType::TableRow(vec!["a".into(), "".into()]));
+        assert!(is_table_separator(&["---".into(), ":-:".into()]));
+        assert!(!is_table_separator(&["---".into(), "a".into()]));
+    }
 }
@@ -3,6 +3,8 @@
 extern crate tempdir;
 
 use std::env;
+use std::fs::{self, File};
+use std::io::Write;
 use std::path::PathBuf;
 use std::process::Command;
 
@@ -20,7 +22,12 @@ impl TestEnv {
 
         // Determine binary path
         let lib_path = env::current_exe().unwrap();
-        let bin_dir = lib_path.parent().unwrap();
+        let mut bin_dir = lib_path.parent().unwrap();
+        // Newer Cargo versions keep test binaries in `deps` and the binary
+        // itself in the directory above
+        if bin_dir.ends_with("deps") && !bin_dir.join("tldr").exists() {
+            bin_dir = bin_dir.parent().unwrap();
+        }
         let bin_path = bin_dir.join("tldr");
 
         TestEnv {
@@ -75,3 +82,23 @@ fn test_update_cache() {
                       .expect(&format!("Could not launch tldr binary ({:?})", &testenv.bin_path));
     assert_eq!(out3.status.success(), true);
 }
+
+#[test]
+fn test_render_dir_to_html_file() {
+    let testenv = TestEnv::new();
+    let page_dir = testenv.cache_dir.path().join("pages");
+    fs::create_dir_all(&page_dir).unwrap();
+    File::create(page_dir.join("tar.md")).unwrap().write_all(b"# tar\n\n> Archiving utility.\n").unwrap();
+    let output_file = testenv.cache_dir.path().join("pages.html");
+
+    let out = testenv.cmd()
+                     .arg("--render-dir").arg(&page_dir)
+                     .arg("--output-file").arg(&output_file)
+                     .output()
+                     .unwrap_or_else(|e| panic!("Could not launch tldr binary ({:?}): {}", &testenv.bin_path, e));
+    assert!(!out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(stdout, "Only a single page can be written to an HTML file. \
+                        Use `tldr export-site` to export several pages.\n");
+    assert!(!output_file.exists());
+}